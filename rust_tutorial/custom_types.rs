@@ -1,8 +1,5 @@
-#[derive(Debug)]
-struct Person<'a> {
-    name: &'a str,
-    age: u8,
-}
+// A struct: `Person` lives in the records crate (see records/), shared with helloworld.rs
+use records::Person;
 
 // A unit struct - useful for generics
 struct Nil;
@@ -10,47 +7,20 @@ struct Nil;
 // A tuple struct
 struct Pair(i32, f32);
 
-// A struct with two fields
-#[derive(Debug)]
-struct Point {
-    x: f32,
-    y: f32,
-}
-
-// Structs can be reused as fields of another struct
-#[derive(Debug)]
-struct Rectangle {
-    p1: Point,
-    p2: Point,
-}
-
-impl Rectangle {
-    fn rect_area(self) -> f32 {
-        let Rectangle { p1, p2 } = self;
-
-        let Point { x: x0, y: y0 } = p1;
-        let Point { x: x1, y: y1 } = p2;
+// A struct with two fields, and structs reused as fields of another struct: `Point` and
+// `Rectangle` live in the geometry crate (see geometry/), shared with functions.rs and generics.rs
+//
+// $ (cd geometry && cargo build) && (cd list && cargo build) && (cd records && cargo build)
+// $ rustc custom_types.rs --extern geometry=geometry/target/debug/libgeometry.rlib \
+//     --extern list=list/target/debug/liblist.rlib \
+//     --extern records=records/target/debug/librecords.rlib -L dependency=records/target/debug/deps
+extern crate geometry;
 
-        (x1 - x0) * (y1 - y0)
-    }
-}
-
-fn square(p : Point, l : f32) -> Rectangle {
-    let Point { x, y } = p;
-    Rectangle {
-        p1 : Point { x, y },
-        p2 : Point { x: x + l, y: y + l},
-    }
-}
+use geometry::{square, Point, Rectangle};
 
-// each valid struct type is a valid enum
-enum WebEvent {
-    PageLoad,
-    PageUnload,
-    KeyPress(char),
-    Paste(String),
-    Click { x: i64, y: i64 },
-}
+// An enum, where each valid struct type is a valid variant: `WebEvent` lives in the records
+// crate, which also reads and writes logs of them
+use records::WebEvent;
 
 fn inspect(event: WebEvent) {
     match event {
@@ -72,48 +42,19 @@ enum Nbr {
     One,
 }
 
-#[allow(dead_code)]
-enum Color {
-    Red = 0xff0000,
-    Green = 0x00ff00,
-    Blue = 0x0000ff,
-}
+// Named colors used to be a C-like enum here, `Red = 0xff0000` and so on; they are constants of
+// the `Color` of the records crate (see records/) now, shared with helloworld.rs
+extern crate records;
 
-#[allow(dead_code)]
-enum List {
-    Cons(u32, Box<List>),
-    Nil,
-}
+use records::Color;
 
-#[allow(dead_code)]
-impl List {
-    fn new() -> List {
-        List::Nil
-    }
+// A linked list used to be an enum here, `Cons(u32, Box<List>)` or `Nil`, where each element
+// boxes (allocates in the heap) the rest of the list. It grew into the generic `List<T>` of the
+// list crate (see list/), whose elements share the rest of the list through an `Rc` instead, so
+// that adding an element to a list leaves the list as it was.
+extern crate list;
 
-    // All values in Rust are stack allocated by default.
-    // Values can be boxed (allocated in the heap) by creating a Box<T>.
-    // A box is a smart pointer to a heap allocated value of type T.
-    // When a box goes out of scope, its destructor is called, the inner object is destroyed,
-    //   and the memory in the heap is freed.
-    fn add(self, elem: u32) -> List {
-        List::Cons(elem, Box::new(self))
-    }
-
-    fn len(&self) -> u32 {
-        // Boxed values can be dereferenced using the * operator;
-        //   this removes one layer of indirection.
-
-        // `self` has type `&List`, so `*self` has type `List`
-        // matching over concrete type is preferred
-        match *self {
-            // can't take ownership of tail since `self` is borrowed: &
-            // instead take a reference to the tail
-            List::Cons(_, ref tail) => 1 + tail.len(),
-            List::Nil => 0
-        }
-    }
-}
+use list::List;
 
 // mulable global variable - need to be typed
 static _LANGUAGE: &str = "Rust";
@@ -123,7 +64,7 @@ const _THRESHOLD: i32 = 10;
 
 fn main() {
     // Create struct with field init shorthand
-    let name = "Peter";
+    let name = "Peter".to_owned();
     let age = 27;
     let peter = Person { name, age };
 
@@ -131,7 +72,7 @@ fn main() {
     println!("{:?}", peter);
 
     // Instantiate a `Point`
-    let point: Point = Point { x: 0.3, y: 0.4 };
+    let point: Point<f32> = Point { x: 0.3, y: 0.4 };
 
     // Access the fields of the point
     println!("point coordinates: ({}, {})", point.x, point.y);
@@ -144,10 +85,10 @@ fn main() {
     let Point { x: my_x, y: my_y } = point;
     println!("{}", my_x);
 
-    let _rectangle = Rectangle {
-        p1: Point { x: my_y, y: my_x }, // can instantiate as an expression as well
-        p2: point,
-    };
+    let _rectangle = Rectangle::new(
+        Point { x: my_y, y: my_x }, // can instantiate as an expression as well
+        point,
+    );
 
     // Instantiate a unit struct
     let _nil = Nil;
@@ -164,11 +105,11 @@ fn main() {
     println!("pair contains {:?} and {:?}", integer, decimal);
 
     // Activity 1 & 2 - structs
-    let rect = Rectangle {
-        p1 : Point { x: 0f32, y: 0f32},
-        p2 : Point { x: 2f32, y: 2f32},
-    };
-    println!("{}", rect.rect_area());
+    let rect = Rectangle::new(Point { x: 0f32, y: 0f32}, Point { x: 2f32, y: 2f32});
+    println!("{}", rect.area());
+    // the corners are normalized, so the area is the same whatever their order; the area spanned
+    // from one corner to the other keeps the orientation
+    println!("{}", Rectangle::signed_area(Point { x: 2f32, y: 0f32}, Point { x: 0f32, y: 2f32}));
     println!("{:#?}", square(Point{ x: 0.0, y: 0.0}, 2.0));
 
     // enums
@@ -193,4 +134,15 @@ fn main() {
     // can be used as C-like enum
     println!("zero is {}", Nbr::Zero as i32);
     println!("one  is {}", Nbr::One as i32);
+
+    // named colors
+    for color in [Color::RED, Color::LIME, Color::BLUE].iter() {
+        println!("{} is 0x{:X}", color.name().unwrap(), color);
+    }
+
+    // linked lists
+    let tail = List::new().add(3).add(2);
+    let list = tail.add(1);
+    println!("{} has {} elements, and shares {} with {}", list, list.len(), tail, tail.add(0));
+    println!("reversed: {}, squared: {}", list.reverse(), list.map(|x| x * x));
 }
@@ -1,47 +1,13 @@
-struct Point {
-    x: f64,
-    y: f64,
-}
-
-impl Point {
-    fn origin() -> Point {
-        Point { x: 0.0, y: 0.0 }
-    }
-
-    fn new(x: f64, y: f64) -> Point {
-        Point { x: x, y: y }
-    }
-}
+// `Point` and `Rectangle`, with their methods, live in the geometry crate (see geometry/):
+// - `Point::origin` and `Point::new` are static methods
+// - `Rectangle::area` and `Rectangle::perimeter` take `&self`, i.e. borrow the rectangle
+// - `Rectangle::translate` takes `&mut self`, i.e. borrows it mutably
+//
+// $ (cd geometry && cargo build)
+// $ rustc functions.rs --extern geometry=geometry/target/debug/libgeometry.rlib
+extern crate geometry;
 
-struct Rectangle {
-    p1: Point,
-    p2: Point,
-}
-
-impl Rectangle {
-    // &self is sugar for self: &Self
-    // i.e. we borrow self
-    fn area(&self) -> f64 {
-        let Point {x, y} = self.p1;
-        let Point { x: x1, y: y1 } = self.p2;
-        ((x1 - x) * (y1 - y)).abs()
-    }
-
-    fn perimeter(&self) -> f64 {
-        let Point {x, y} = self.p1;
-        let Point { x: x1, y: y1 } = self.p2;
-        2.0 * ((x1 - x).abs() + (y1 - y).abs())
-    }
-
-    // &mut self is sugar to `self: &mut Self`
-    fn translate(&mut self, x: f64) {
-        // pass each value as a mutable reference
-        for c in vec![&mut self.p1.x, &mut self.p2.x, &mut self.p1.y, &mut self.p2.y].iter_mut() {
-            // dereference the iterator and the mutable reference
-            **c += x;
-        }
-    }
-}
+use geometry::{Point, Rectangle};
 
 // pair of 2 heap allocate integers
 struct Pair(Box<i32>, Box<i32>);
@@ -55,11 +21,8 @@ impl Pair {
 }
 
 fn main() {
-    let mut rectangle = Rectangle {
-        // Static methods are called using double colons
-        p1: Point::origin(),
-        p2: Point::new(3.0, 4.0),
-    };
+    // Static methods are called using double colons
+    let mut rectangle = Rectangle::new(Point::origin(), Point::new(3.0, 4.0));
 
     // Instance methods are called using the dot operator
     // Note that the first argument `&self` is implicitly passed, i.e.
@@ -67,8 +30,8 @@ fn main() {
     println!("Rectangle perimeter: {}", rectangle.perimeter());
     println!("Rectangle area: {}", rectangle.area());
 
-    rectangle.translate(1.0);
-    let Rectangle { p1: _, p2: Point {x, y: _} } = rectangle;
+    rectangle.translate(1.0, 1.0);
+    let Point { x, y: _ } = rectangle.max();
     println!("{:?}", x);
 
     let pair = Pair(Box::new(1), Box::new(2));
@@ -1,5 +1,11 @@
+// $ (cd geometry && cargo build)
+// $ rustc generics.rs --extern geometry=geometry/target/debug/libgeometry.rlib
+extern crate geometry;
+
 use std::fmt::Debug;
 
+use geometry::{Circle, HasArea, Point, Polygon, Rectangle, Shape, Triangle};
+
 // a generic tuple struct
 struct SGen<T>(T);
 
@@ -28,18 +34,8 @@ impl <T, U> DoubleDrop<T> for U {
 }
 
 // Bounds
-trait HasArea {
-    fn area(&self) -> f64;
-}
-
-impl HasArea for Rectangle {
-    fn area(&self) -> f64 {
-        self.length * self.height
-    }
-}
-
-#[derive(Debug)]
-struct Rectangle { length: f64, height: f64 }
+// `HasArea` comes from the geometry crate, where it is implemented for rectangles, circles,
+// triangles and polygons, together with `HasPerimeter`, `BoundingBox` and `Contains`
 
 fn print_debug<T: Debug>(t: &T) {
     println!("{:?}", t);
@@ -105,11 +101,23 @@ fn main() {
     // null is not accessible here due to move semantics
 
     // bounds
-    let rectangle = Rectangle { length: 3.0, height: 4.0 };
+    let rectangle = Rectangle::new(Point::origin(), Point::new(3.0, 4.0));
     println!("{:?}", area(&rectangle));
     print_debug(&rectangle);
     print_debug_area(&rectangle);
 
+    // trait objects: shapes of different types behind the same `Shape` trait
+    let shapes: Vec<Box<dyn Shape>> = vec![
+        Box::new(rectangle),
+        Box::new(Circle::new(Point::origin(), 1.0).unwrap()),
+        Box::new(Triangle::new(Point::origin(), Point::new(4.0, 0.0), Point::new(0.0, 3.0)).unwrap()),
+        Box::new(Polygon::new(vec![Point::origin(), Point::new(2.0, 0.0), Point::new(2.0, 2.0), Point::new(1.0, 3.0), Point::new(0.0, 2.0)]).unwrap()),
+    ];
+    for shape in shapes.iter() {
+        println!("area {:.2}, perimeter {:.2}, contains (1, 1): {}",
+                 shape.area(), shape.perimeter(), shape.contains(&Point::new(1.0, 1.0)));
+    }
+
     let vec = vec![1, 2, 3];
     vec.print_in_option();
 
@@ -0,0 +1,109 @@
+// Times the R-tree against a linear scan over the same rectangles, and checks that both give the
+// same answers; the unit tests in src/index.rs check the corner cases:
+// cargo run --release --example index [rectangles] [queries]
+use std::env;
+use std::time::{Duration, Instant};
+
+use geometry::{Point, RTree, Rectangle};
+
+// the rectangles are scattered over [0, SIZE) x [0, SIZE), with sides up to MAX_SIDE
+const SIZE: f64 = 1000.0;
+const MAX_SIDE: f64 = 20.0;
+const K: usize = 10;
+
+// a xorshift generator of numbers in [0, 1)
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn point(&mut self) -> Point<f64> {
+        Point::new(self.next() * SIZE, self.next() * SIZE)
+    }
+
+    fn rectangle(&mut self, max_side: f64) -> Rectangle<f64> {
+        let p = self.point();
+        Rectangle::new(p, Point::new(p.x + self.next() * max_side, p.y + self.next() * max_side))
+    }
+}
+
+fn distance(rect: &Rectangle<f64>, p: &Point<f64>) -> f64 {
+    let (lo, hi) = (rect.min(), rect.max());
+    let dx = (lo.x - p.x).max(p.x - hi.x).max(0.0);
+    let dy = (lo.y - p.y).max(p.y - hi.y).max(0.0);
+    dx.hypot(dy)
+}
+
+fn sorted(found: Vec<&usize>) -> Vec<usize> {
+    let mut found = found.into_iter().cloned().collect::<Vec<_>>();
+    found.sort();
+    found
+}
+
+// runs `query` on each input, returning the answers and the time they took
+fn time<I, T, F: FnMut(&I) -> T>(inputs: &[I], mut query: F) -> (Vec<T>, Duration) {
+    let start = Instant::now();
+    let answers = inputs.iter().map(&mut query).collect();
+    (answers, start.elapsed())
+}
+
+fn main() {
+    let mut args = env::args().skip(1).map(|arg| arg.parse::<usize>().expect("expected a number"));
+    let count = args.next().unwrap_or(100_000);
+    let queries = args.next().unwrap_or(1000);
+
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    let rects = (0..count).map(|_| random.rectangle(MAX_SIDE)).collect::<Vec<_>>();
+    let points = (0..queries).map(|_| random.point()).collect::<Vec<_>>();
+    let windows = (0..queries).map(|_| random.rectangle(5.0 * MAX_SIDE)).collect::<Vec<_>>();
+
+    let start = Instant::now();
+    let tree = RTree::new(rects.iter().cloned().zip(0..).collect());
+    println!("{} rectangles indexed in {:?}", tree.len(), start.elapsed());
+
+    let (expected, linear) = time(&points, |p| {
+        (0..count).filter(|&i| rects[i].contains(p)).collect::<Vec<_>>()
+    });
+    let (found, indexed) = time(&points, |p| sorted(tree.containing(p)));
+    assert_eq!(found, expected);
+    println!("point-in:       linear {:>12?}, indexed {:>12?}", linear, indexed);
+
+    let (expected, linear) = time(&windows, |w| {
+        (0..count).filter(|&i| rects[i].intersection(w).is_some()).collect::<Vec<_>>()
+    });
+    let (found, indexed) = time(&windows, |w| sorted(tree.overlapping(w)));
+    assert_eq!(found, expected);
+    println!("window-overlap: linear {:>12?}, indexed {:>12?}", linear, indexed);
+
+    // rectangles at the same distance may come in any order, so only the distances are compared
+    let (expected, linear) = time(&points, |p| {
+        let mut distances = rects.iter().map(|rect| distance(rect, p)).collect::<Vec<_>>();
+        if distances.len() > K {
+            distances.select_nth_unstable_by(K, f64::total_cmp);
+            distances.truncate(K);
+        }
+        distances.sort_by(f64::total_cmp);
+        distances
+    });
+    let (found, indexed) = time(&points, |p| {
+        tree.nearest(p, K).into_iter().map(|(d, i)| {
+            assert_eq!(d, distance(&rects[*i], p));
+            d
+        }).collect::<Vec<_>>()
+    });
+    assert_eq!(found, expected);
+    println!("{}-nearest:     linear {:>12?}, indexed {:>12?}", K, linear, indexed);
+
+    // points are rectangles with no area
+    let tree = RTree::from_points(points.iter().cloned().zip(0..).collect());
+    let window = Rectangle::new(Point::new(0.0, 0.0), Point::new(SIZE / 2.0, SIZE / 2.0));
+    let expected = (0..queries).filter(|&i| window.contains(&points[i])).collect::<Vec<_>>();
+    assert_eq!(sorted(tree.overlapping(&window)), expected);
+    assert_eq!(tree.nearest(&points[0], 1), vec![(0.0, &0)]);
+    assert!(RTree::<()>::new(Vec::new()).nearest(&points[0], K).is_empty());
+}
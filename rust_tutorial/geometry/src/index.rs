@@ -0,0 +1,310 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::point::{max, Point};
+use crate::rectangle::Rectangle;
+
+// children of each node, and entries of each leaf
+const CAPACITY: usize = 8;
+
+// An R-tree: a tree of rectangles where each node is the bounding box of its children, and the
+// leaves hold the indexed rectangles. A query only descends into the nodes whose bounding box
+// can have an answer, so it looks at a few leaves instead of every rectangle.
+//
+// The tree is built once, from all the rectangles, by sort-tile-recursive packing: the entries
+// are sorted by the x of their center and cut into vertical slices, each slice is sorted by y and
+// cut into leaves of `CAPACITY` entries, and the leaves are packed into nodes the same way, level
+// by level. Neighbouring rectangles end up in the same leaves, so the boxes overlap little.
+pub struct RTree<V> {
+    root: Option<Node<V>>,
+    len: usize,
+}
+
+enum Node<V> {
+    Leaf(Vec<(Rectangle<f64>, V)>),
+    Inner(Vec<(Rectangle<f64>, Node<V>)>),
+}
+
+impl<V> RTree<V> {
+    pub fn new(entries: Vec<(Rectangle<f64>, V)>) -> RTree<V> {
+        let len = entries.len();
+        if entries.is_empty() {
+            return RTree { root: None, len };
+        }
+        let mut level = pack(entries).into_iter()
+            .map(|entries| (bounds(&entries), Node::Leaf(entries)))
+            .collect::<Vec<_>>();
+        while level.len() > 1 {
+            level = pack(level).into_iter()
+                .map(|children| (bounds(&children), Node::Inner(children)))
+                .collect();
+        }
+        let (_, root) = level.pop().unwrap();
+        RTree { root: Some(root), len }
+    }
+
+    // points are indexed as rectangles with no area
+    pub fn from_points(points: Vec<(Point<f64>, V)>) -> RTree<V> {
+        RTree::new(points.into_iter().map(|(p, value)| (Rectangle::new(p, p), value)).collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // the values of the rectangles containing `p`, edges included
+    pub fn containing(&self, p: &Point<f64>) -> Vec<&V> {
+        self.search(|rect| rect.contains(p))
+    }
+
+    // the values of the rectangles sharing at least a point with `window`
+    pub fn overlapping(&self, window: &Rectangle<f64>) -> Vec<&V> {
+        self.search(|rect| rect.intersection(window).is_some())
+    }
+
+    // The values of the `k` rectangles closest to `p`, closest first, with their distance to `p`;
+    // rectangles containing `p` are at distance 0.
+    //
+    // Nodes and entries wait in a queue ordered by their distance to `p`. A node is never closer
+    // than its bounding box, so once an entry is at the front of the queue, nothing left in the
+    // queue is closer and the entry is the next answer.
+    pub fn nearest(&self, p: &Point<f64>, k: usize) -> Vec<(f64, &V)> {
+        let mut nearest = Vec::new();
+        let mut queue = BinaryHeap::new();
+        if let Some(root) = self.root.as_ref() {
+            queue.push(Queued { distance: 0.0, item: Item::Node(root) });
+        }
+        while nearest.len() < k {
+            match queue.pop() {
+                Some(Queued { distance, item: Item::Value(value) }) => nearest.push((distance, value)),
+                Some(Queued { item: Item::Node(Node::Leaf(entries)), .. }) => {
+                    for (rect, value) in entries.iter() {
+                        queue.push(Queued { distance: distance_to(rect, p), item: Item::Value(value) });
+                    }
+                },
+                Some(Queued { item: Item::Node(Node::Inner(children)), .. }) => {
+                    for (rect, child) in children.iter() {
+                        queue.push(Queued { distance: distance_to(rect, p), item: Item::Node(child) });
+                    }
+                },
+                None => break,
+            }
+        }
+        nearest
+    }
+
+    // the values of the entries whose rectangle matches, descending only into the nodes whose
+    // bounding box matches; `matches` must hold for a box whenever it holds for a rectangle
+    // inside it
+    fn search<F: Fn(&Rectangle<f64>) -> bool>(&self, matches: F) -> Vec<&V> {
+        let mut found = Vec::new();
+        let mut stack = self.root.iter().collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Leaf(entries) => {
+                    found.extend(entries.iter().filter(|(rect, _)| matches(rect)).map(|(_, value)| value));
+                },
+                Node::Inner(children) => {
+                    stack.extend(children.iter().filter(|(rect, _)| matches(rect)).map(|(_, child)| child));
+                },
+            }
+        }
+        found
+    }
+}
+
+// Sort-tile-recursive packing of one level into groups of at most `CAPACITY`
+fn pack<T>(mut entries: Vec<(Rectangle<f64>, T)>) -> Vec<Vec<(Rectangle<f64>, T)>> {
+    let groups = entries.len().div_ceil(CAPACITY);
+    let slices = (groups as f64).sqrt().ceil() as usize;
+    let per_slice = slices * CAPACITY;
+
+    entries.sort_by(|(a, _), (b, _)| center(a).x.total_cmp(&center(b).x));
+    let mut packed = Vec::with_capacity(groups);
+    while !entries.is_empty() {
+        let rest = entries.split_off(per_slice.min(entries.len()));
+        let mut slice = std::mem::replace(&mut entries, rest);
+        slice.sort_by(|(a, _), (b, _)| center(a).y.total_cmp(&center(b).y));
+        while !slice.is_empty() {
+            let rest = slice.split_off(CAPACITY.min(slice.len()));
+            packed.push(std::mem::replace(&mut slice, rest));
+        }
+    }
+    packed
+}
+
+fn center(rect: &Rectangle<f64>) -> Point<f64> {
+    let (lo, hi) = (rect.min(), rect.max());
+    Point::new((lo.x + hi.x) / 2.0, (lo.y + hi.y) / 2.0)
+}
+
+// the bounding box of a non-empty group
+fn bounds<T>(entries: &[(Rectangle<f64>, T)]) -> Rectangle<f64> {
+    entries[1..].iter().fold(entries[0].0, |bounds, (rect, _)| bounds.union(rect))
+}
+
+// the distance from `p` to the closest point of `rect`
+fn distance_to(rect: &Rectangle<f64>, p: &Point<f64>) -> f64 {
+    let (lo, hi) = (rect.min(), rect.max());
+    let dx = max(max(lo.x - p.x, p.x - hi.x), 0.0);
+    let dy = max(max(lo.y - p.y, p.y - hi.y), 0.0);
+    dx.hypot(dy)
+}
+
+enum Item<'a, V> {
+    Node(&'a Node<V>),
+    Value(&'a V),
+}
+
+// an item of the queue of `nearest`; `BinaryHeap` pops the largest, so the order is reversed
+struct Queued<'a, V> {
+    distance: f64,
+    item: Item<'a, V>,
+}
+
+impl<V> Ord for Queued<'_, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+impl<V> PartialOrd for Queued<'_, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<V> PartialEq for Queued<'_, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<V> Eq for Queued<'_, V> {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{distance_to, RTree};
+    use crate::point::Point;
+    use crate::rectangle::Rectangle;
+
+    // a xorshift generator of numbers in [0, size)
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self, size: f64) -> f64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64 * size
+        }
+
+        fn point(&mut self) -> Point<f64> {
+            Point::new(self.next(100.0), self.next(100.0))
+        }
+
+        fn rectangle(&mut self, max_side: f64) -> Rectangle<f64> {
+            let p = self.point();
+            Rectangle::new(p, Point::new(p.x + self.next(max_side), p.y + self.next(max_side)))
+        }
+    }
+
+    fn sorted(found: Vec<&usize>) -> Vec<usize> {
+        let mut found = found.into_iter().cloned().collect::<Vec<_>>();
+        found.sort();
+        found
+    }
+
+    // compares every query of `tree`, built from `rects`, with a linear scan over `rects`
+    fn check(rects: &[Rectangle<f64>], random: &mut Random) {
+        let tree = RTree::new(rects.iter().cloned().zip(0..).collect());
+        assert_eq!(tree.len(), rects.len());
+        for _ in 0..100 {
+            let p = random.point();
+            let expected = (0..rects.len()).filter(|&i| rects[i].contains(&p)).collect::<Vec<_>>();
+            assert_eq!(sorted(tree.containing(&p)), expected);
+
+            let window = random.rectangle(30.0);
+            let expected = (0..rects.len()).filter(|&i| rects[i].intersection(&window).is_some()).collect::<Vec<_>>();
+            assert_eq!(sorted(tree.overlapping(&window)), expected);
+
+            // rectangles at the same distance may come in any order, so only the distances are
+            // compared, and each answer must be at the distance given for it
+            for k in [0, 1, 5, rects.len() + 1] {
+                let nearest = tree.nearest(&p, k);
+                let mut expected = rects.iter().map(|rect| distance_to(rect, &p)).collect::<Vec<_>>();
+                expected.sort_by(f64::total_cmp);
+                expected.truncate(k);
+                assert_eq!(nearest.iter().map(|(d, _)| *d).collect::<Vec<_>>(), expected);
+                assert!(nearest.iter().all(|(d, i)| *d == distance_to(&rects[**i], &p)));
+                let values = sorted(nearest.into_iter().map(|(_, i)| i).collect());
+                assert!(values.windows(2).all(|pair| pair[0] < pair[1]), "{:?} answered twice", values);
+            }
+        }
+    }
+
+    #[test]
+    fn queries_match_a_linear_scan() {
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+        // up to several levels of nodes, and sizes around the capacity of a leaf
+        for count in [1, 7, 8, 9, 64, 65, 1000] {
+            let rects = (0..count).map(|_| random.rectangle(10.0)).collect::<Vec<_>>();
+            check(&rects, &mut random);
+        }
+    }
+
+    #[test]
+    fn duplicate_rectangles() {
+        let mut random = Random(42);
+        let rect = Rectangle::new(Point::new(10.0, 10.0), Point::new(20.0, 20.0));
+        let mut rects = vec![rect; 20];
+        check(&rects, &mut random);
+
+        // mixed with others
+        rects.extend((0..100).map(|_| random.rectangle(10.0)));
+        rects.extend(vec![rect; 20]);
+        check(&rects, &mut random);
+
+        let tree = RTree::new(rects.iter().cloned().zip(0..).collect());
+        assert!(tree.containing(&Point::new(15.0, 15.0)).len() >= 40);
+    }
+
+    #[test]
+    fn points() {
+        let mut random = Random(7);
+        let points = (0..200).map(|_| random.point()).collect::<Vec<_>>();
+        let tree = RTree::from_points(points.iter().cloned().zip(0..).collect());
+        let rects = points.iter().map(|p| Rectangle::new(*p, *p)).collect::<Vec<_>>();
+        check(&rects, &mut random);
+        // a point is at distance 0 from itself
+        assert_eq!(tree.nearest(&points[3], 1), vec![(0.0, &3)]);
+        assert_eq!(tree.containing(&points[3]), vec![&3]);
+    }
+
+    #[test]
+    fn single_entry() {
+        let rect = Rectangle::new(Point::new(0.0, 0.0), Point::new(2.0, 1.0));
+        let tree = RTree::new(vec![(rect, "only")]);
+        assert_eq!((tree.len(), tree.is_empty()), (1, false));
+        assert_eq!(tree.containing(&Point::new(2.0, 1.0)), vec![&"only"]);
+        assert!(tree.containing(&Point::new(2.0, 1.5)).is_empty());
+        assert_eq!(tree.nearest(&Point::new(5.0, 5.0), 3), vec![(5.0, &"only")]);
+        check(&[rect], &mut Random(1));
+    }
+
+    #[test]
+    fn empty_tree() {
+        let tree = RTree::<usize>::new(Vec::new());
+        assert_eq!((tree.len(), tree.is_empty()), (0, true));
+        let p = Point::new(1.0, 1.0);
+        assert!(tree.containing(&p).is_empty());
+        assert!(tree.overlapping(&Rectangle::new(Point::new(0.0, 0.0), Point::new(100.0, 100.0))).is_empty());
+        assert!(tree.nearest(&p, 0).is_empty());
+        assert!(tree.nearest(&p, 10).is_empty());
+    }
+}
@@ -0,0 +1,16 @@
+// The `Point` and `Rectangle` used by the tutorial binaries (custom_types.rs, functions.rs and
+// generics.rs), generic over the type of their coordinates, other shapes measured in `f64`,
+// and an index over rectangles.
+mod index;
+mod point;
+#[cfg(test)]
+mod properties;
+mod rectangle;
+#[cfg(feature = "serde")]
+mod serialization;
+mod shape;
+
+pub use index::RTree;
+pub use point::{Coordinate, CoordinateError, Point};
+pub use rectangle::{square, Rectangle};
+pub use shape::{BoundingBox, Circle, Contains, HasArea, HasPerimeter, Polygon, Shape, ShapeError, Triangle};
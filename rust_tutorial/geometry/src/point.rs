@@ -0,0 +1,111 @@
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::ops::{Add, Mul, Sub};
+
+// What a coordinate must support, implemented for the integer and float types.
+// `Default` gives the zero of the type, e.g. for the origin.
+pub trait Coordinate: Copy + Debug + PartialOrd + Default + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {
+    // Any integer is a valid coordinate; a float is not when NaN or infinite, as it would make
+    // comparisons, and so the normalization of rectangles, meaningless.
+    fn validate(self) -> Result<Self, CoordinateError> {
+        Ok(self)
+    }
+}
+
+macro_rules! integers {
+    ($($t:ty),*) => { $(impl Coordinate for $t {})* };
+}
+
+macro_rules! floats {
+    ($($t:ty),*) => {
+        $(impl Coordinate for $t {
+            fn validate(self) -> Result<Self, CoordinateError> {
+                if self.is_nan() {
+                    Err(CoordinateError::NaN)
+                } else if self.is_infinite() {
+                    Err(CoordinateError::Infinite)
+                } else {
+                    Ok(self)
+                }
+            }
+        })*
+    };
+}
+
+integers!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+floats!(f32, f64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinateError {
+    NaN,
+    Infinite,
+}
+
+impl Display for CoordinateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CoordinateError::NaN => write!(f, "coordinate is NaN"),
+            CoordinateError::Infinite => write!(f, "coordinate is infinite"),
+        }
+    }
+}
+
+impl Error for CoordinateError {}
+
+// A struct with two fields
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: Coordinate> Point<T> {
+    pub fn origin() -> Point<T> {
+        Point { x: T::default(), y: T::default() }
+    }
+
+    pub fn new(x: T, y: T) -> Point<T> {
+        Point { x, y }
+    }
+
+    pub fn translate(&mut self, dx: T, dy: T) {
+        self.x = self.x + dx;
+        self.y = self.y + dy;
+    }
+}
+
+// `PartialOrd` has no `min`/`max`
+pub(crate) fn min<T: PartialOrd>(a: T, b: T) -> T {
+    if b < a { b } else { a }
+}
+
+pub(crate) fn max<T: PartialOrd>(a: T, b: T) -> T {
+    if b > a { b } else { a }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Coordinate, CoordinateError, Point};
+
+    #[test]
+    fn origin() {
+        assert_eq!(Point::<i32>::origin(), Point::new(0, 0));
+        assert_eq!(Point::<f64>::origin(), Point::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn translate() {
+        let mut p = Point::new(1.5, -2.0);
+        p.translate(0.5, 2.0);
+        assert_eq!(p, Point::new(2.0, 0.0));
+    }
+
+    #[test]
+    fn validate() {
+        assert_eq!(3u8.validate(), Ok(3));
+        assert_eq!((-1.5f64).validate(), Ok(-1.5));
+        assert_eq!(f32::NAN.validate(), Err(CoordinateError::NaN));
+        assert_eq!(f64::NEG_INFINITY.validate(), Err(CoordinateError::Infinite));
+    }
+}
@@ -0,0 +1,141 @@
+// Properties of rectangles over random corners, given in any order, checked with quickcheck.
+use std::fmt::Debug;
+
+use quickcheck::{Arbitrary, Gen, QuickCheck};
+
+use crate::point::{CoordinateError, Point};
+use crate::rectangle::Rectangle;
+
+const CASES: u64 = 10_000;
+
+// a corner in [-1000, 1000], with a few repeated values so that corners often share a coordinate
+#[derive(Clone, Copy, Debug)]
+struct Corner(Point<f64>);
+
+impl Arbitrary for Corner {
+    fn arbitrary(g: &mut Gen) -> Corner {
+        let mut float = || {
+            if u8::arbitrary(g) % 8 == 0 {
+                (u8::arbitrary(g) % 3) as f64
+            } else {
+                (i32::arbitrary(g) % 1_000_001) as f64 / 1000.0
+            }
+        };
+        Corner(Point::new(float(), float()))
+    }
+}
+
+// a corner in [-100, 100], where the properties hold exactly
+#[derive(Clone, Copy, Debug)]
+struct IntCorner(Point<i64>);
+
+impl Arbitrary for IntCorner {
+    fn arbitrary(g: &mut Gen) -> IntCorner {
+        let mut int = || (i16::arbitrary(g) % 101) as i64;
+        IntCorner(Point::new(int(), int()))
+    }
+}
+
+// runs a property of two arguments on `CASES` random cases
+fn check<A: Arbitrary + Debug, B: Arbitrary + Debug>(property: fn(A, B)) {
+    QuickCheck::new().tests(CASES).quickcheck(property);
+}
+
+#[test]
+fn normalization() {
+    check(|Corner(p1), Corner(p2)| {
+        let r = Rectangle::new(p1, p2);
+        let (lo, hi) = (r.min(), r.max());
+        assert!(lo.x <= hi.x && lo.y <= hi.y);
+        assert!(r.width() >= 0.0 && r.height() >= 0.0 && r.area() >= 0.0);
+
+        // the same rectangle from any pair of opposite corners, in any order
+        let p3 = Point::new(p1.x, p2.y);
+        let p4 = Point::new(p2.x, p1.y);
+        for (a, b) in [(p2, p1), (p3, p4), (p4, p3)].iter() {
+            assert_eq!(Rectangle::new(*a, *b), r);
+        }
+        for p in [p1, p2, p3, p4].iter() {
+            assert!(r.contains(p));
+        }
+    });
+}
+
+#[test]
+fn signed_area() {
+    check(|Corner(p1), Corner(p2)| {
+        let r = Rectangle::new(p1, p2);
+        let signed = Rectangle::signed_area(p1, p2);
+        assert_eq!(signed.abs(), r.area());
+        assert_eq!(Rectangle::signed_area(p2, p1), signed);
+        // mirroring one side flips the sign
+        assert_eq!(Rectangle::signed_area(Point::new(p2.x, p1.y), Point::new(p1.x, p2.y)), -signed);
+        let flipped = (p2.x < p1.x) != (p2.y < p1.y);
+        assert!(if flipped { signed <= 0.0 } else { signed >= 0.0 });
+    });
+}
+
+#[test]
+fn union() {
+    check(|(Corner(p1), Corner(p2)), (Corner(p3), Corner(p4))| {
+        let (a, b) = (Rectangle::new(p1, p2), Rectangle::new(p3, p4));
+        let union = a.union(&b);
+        assert!(union.contains_rectangle(&a) && union.contains_rectangle(&b));
+        assert_eq!(union, b.union(&a));
+        assert!(union.area() >= a.area().max(b.area()));
+    });
+}
+
+#[test]
+fn intersection() {
+    check(|(Corner(p1), Corner(p2)), (Corner(p3), Corner(p4))| {
+        let (a, b) = (Rectangle::new(p1, p2), Rectangle::new(p3, p4));
+        assert_eq!(a.intersection(&b), b.intersection(&a));
+        match a.intersection(&b) {
+            Some(common) => {
+                assert!(a.contains_rectangle(&common) && b.contains_rectangle(&common));
+                assert!(common.area() <= a.area().min(b.area()));
+            },
+            None => assert!(!a.contains(&b.min()) && !b.contains(&a.min())),
+        }
+    });
+}
+
+#[test]
+fn translate() {
+    check(|(Corner(p1), Corner(p2)), Corner(by)| {
+        let a = Rectangle::new(p1, p2);
+        let mut moved = a;
+        moved.translate(by.x, by.y);
+        assert_eq!(moved.min(), Point::new(a.min().x + by.x, a.min().y + by.y));
+        assert!((moved.area() - a.area()).abs() <= 1e-6 * a.area().max(1.0));
+    });
+}
+
+// on integers the properties hold exactly
+#[test]
+fn integers() {
+    check(|(IntCorner(p1), IntCorner(p2)), IntCorner(q)| {
+        let r = Rectangle::new(p1, p2);
+        assert_eq!(Rectangle::signed_area(p1, p2).abs(), r.area());
+        assert_eq!(Rectangle::new(p2, p1), r);
+        let mut moved = r;
+        moved.translate(q.x, q.y);
+        assert_eq!(moved.area(), r.area());
+        assert!(moved.contains(&Point::new(p1.x + q.x, p1.y + q.y)));
+    });
+}
+
+#[test]
+fn invalid() {
+    fn property(Corner(p): Corner) {
+        let bad = [(f64::NAN, CoordinateError::NaN), (f64::INFINITY, CoordinateError::Infinite), (f64::NEG_INFINITY, CoordinateError::Infinite)];
+        for (bad, error) in bad.iter() {
+            assert_eq!(Rectangle::try_new(p, Point::new(*bad, p.y)), Err(*error));
+            assert_eq!(Rectangle::try_new(Point::new(p.x, *bad), p), Err(*error));
+        }
+        assert!(Rectangle::try_new(p, p).is_ok());
+    }
+    QuickCheck::new().tests(CASES).quickcheck(property as fn(Corner));
+    assert_eq!(Rectangle::try_new(Point::new(1.0f32, 2.0), Point::new(f32::NAN, 0.0)), Err(CoordinateError::NaN));
+}
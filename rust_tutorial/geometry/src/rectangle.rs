@@ -0,0 +1,226 @@
+use std::ops::Neg;
+
+use crate::point::{max, min, Coordinate, CoordinateError, Point};
+
+// Structs can be reused as fields of another struct.
+//
+// The sides are parallel to the axes. A rectangle is built from any two opposite corners, in any
+// order, and keeps them normalized: `min` has the smallest coordinates and `max` the largest, so
+// widths, heights and areas are never negative, also for unsigned coordinates. The fields are
+// private so that no rectangle can be built without going through the normalization.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle<T> {
+    min: Point<T>,
+    max: Point<T>,
+}
+
+// A square with its bottom left corner at `p`; a negative side puts it on the other side of `p`.
+pub fn square<T: Coordinate>(p: Point<T>, side: T) -> Rectangle<T> {
+    let Point { x, y } = p;
+    Rectangle::new(Point { x, y }, Point { x: x + side, y: y + side })
+}
+
+impl<T: Coordinate> Rectangle<T> {
+    // Panics on a NaN or infinite coordinate; see `try_new`.
+    pub fn new(p1: Point<T>, p2: Point<T>) -> Rectangle<T> {
+        match Rectangle::try_new(p1, p2) {
+            Ok(rectangle) => rectangle,
+            Err(error) => panic!("invalid rectangle corners {:?} and {:?}: {}", p1, p2, error),
+        }
+    }
+
+    pub fn try_new(p1: Point<T>, p2: Point<T>) -> Result<Rectangle<T>, CoordinateError> {
+        for c in [p1.x, p1.y, p2.x, p2.y].iter() {
+            c.validate()?;
+        }
+        Ok(Rectangle {
+            min: Point::new(min(p1.x, p2.x), min(p1.y, p2.y)),
+            max: Point::new(max(p1.x, p2.x), max(p1.y, p2.y)),
+        })
+    }
+
+    // the corner with the smallest coordinates
+    pub fn min(&self) -> Point<T> {
+        self.min
+    }
+
+    // the corner with the largest coordinates
+    pub fn max(&self) -> Point<T> {
+        self.max
+    }
+
+    pub fn width(&self) -> T {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> T {
+        self.max.y - self.min.y
+    }
+
+    // always positive or zero; see `signed_area` for an area that keeps the orientation
+    pub fn area(&self) -> T {
+        self.width() * self.height()
+    }
+
+    pub fn perimeter(&self) -> T {
+        let half = self.width() + self.height();
+        half + half
+    }
+
+    // Moves both corners. Panics if a coordinate becomes NaN or infinite.
+    pub fn translate(&mut self, dx: T, dy: T) {
+        let (mut min, mut max) = (self.min, self.max);
+        min.translate(dx, dy);
+        max.translate(dx, dy);
+        *self = Rectangle::new(min, max);
+    }
+
+    // points on the edges are inside
+    pub fn contains(&self, p: &Point<T>) -> bool {
+        self.min.x <= p.x && p.x <= self.max.x && self.min.y <= p.y && p.y <= self.max.y
+    }
+
+    pub fn contains_rectangle(&self, other: &Rectangle<T>) -> bool {
+        self.contains(&other.min) && self.contains(&other.max)
+    }
+
+    // The common part of two rectangles, if any. Rectangles that only touch have a common edge
+    // or corner, i.e. a rectangle with no area.
+    pub fn intersection(&self, other: &Rectangle<T>) -> Option<Rectangle<T>> {
+        let lo = Point::new(max(self.min.x, other.min.x), max(self.min.y, other.min.y));
+        let hi = Point::new(min(self.max.x, other.max.x), min(self.max.y, other.max.y));
+        if lo.x <= hi.x && lo.y <= hi.y {
+            Some(Rectangle { min: lo, max: hi })
+        } else {
+            None
+        }
+    }
+
+    // The smallest rectangle containing both rectangles
+    pub fn union(&self, other: &Rectangle<T>) -> Rectangle<T> {
+        Rectangle {
+            min: Point::new(min(self.min.x, other.min.x), min(self.min.y, other.min.y)),
+            max: Point::new(max(self.max.x, other.max.x), max(self.max.y, other.max.y)),
+        }
+    }
+}
+
+impl<T: Coordinate + Neg<Output = T>> Rectangle<T> {
+    // The area spanned from `p1` to `p2`, as the corners are given rather than normalized: it is
+    // negative when `p2` is left of `p1` or below it, but not both. Its absolute value is the
+    // `area` of the rectangle with these corners. Only for coordinates that can be negative.
+    pub fn signed_area(p1: Point<T>, p2: Point<T>) -> T {
+        (p2.x - p1.x) * (p2.y - p1.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{square, Rectangle};
+    use crate::point::{CoordinateError, Point};
+
+    #[test]
+    fn squares() {
+        let s = square(Point::new(1, 2), 3);
+        assert_eq!(s, Rectangle::new(Point::new(1, 2), Point::new(4, 5)));
+        assert_eq!(s.width(), s.height());
+        // a negative side puts the square on the other side of the corner
+        assert_eq!(square(Point::new(1, 2), -3), Rectangle::new(Point::new(-2, -1), Point::new(1, 2)));
+    }
+
+    #[test]
+    fn normalization() {
+        // the corners can be given in any order
+        let r = Rectangle::new(Point::new(3.0f32, 0.0), Point::new(0.0, 4.0));
+        assert_eq!((r.min(), r.max()), (Point::new(0.0, 0.0), Point::new(3.0, 4.0)));
+        assert_eq!(r, Rectangle::new(Point::new(0.0, 4.0), Point::new(3.0, 0.0)));
+
+        // also for unsigned coordinates, where `p2 - p1` would underflow without the normalization
+        let r = Rectangle::new(Point::new(5u32, 5), Point::new(2, 1));
+        assert_eq!((r.width(), r.height()), (3, 4));
+    }
+
+    #[test]
+    fn try_new() {
+        let p = Point::new(1.0, 2.0);
+        assert_eq!(Rectangle::try_new(p, Point::new(f64::NAN, 0.0)), Err(CoordinateError::NaN));
+        assert_eq!(Rectangle::try_new(Point::new(0.0, f64::INFINITY), p), Err(CoordinateError::Infinite));
+        assert_eq!(Rectangle::try_new(p, p), Ok(Rectangle::new(p, p)));
+    }
+
+    #[test]
+    #[should_panic(expected = "coordinate is NaN")]
+    fn new_panics_on_nan() {
+        Rectangle::new(Point::new(0.0, 0.0), Point::new(f64::NAN, 1.0));
+    }
+
+    #[test]
+    fn area() {
+        assert_eq!(Rectangle::new(Point::origin(), Point::new(3.0, 4.0)).area(), 12.0);
+        assert_eq!(Rectangle::new(Point::new(5u32, 5), Point::new(2, 1)).area(), 12);
+    }
+
+    #[test]
+    fn perimeter() {
+        assert_eq!(Rectangle::new(Point::new(3.0, 0.0), Point::new(0.0, 4.0)).perimeter(), 14.0);
+        assert_eq!(square(Point::new(0u8, 0), 0).perimeter(), 0);
+    }
+
+    #[test]
+    fn translate() {
+        let mut r = Rectangle::new(Point::new(0, 0), Point::new(2, 3));
+        r.translate(1, -1);
+        assert_eq!(r, Rectangle::new(Point::new(1, -1), Point::new(3, 2)));
+        assert_eq!(r.area(), 6);
+    }
+
+    #[test]
+    fn contains() {
+        let r = Rectangle::new(Point::new(4, 4), Point::new(0, 0));
+        assert!(r.contains(&Point::new(2, 2)));
+        assert!(r.contains(&Point::new(0, 4)));
+        assert!(!r.contains(&Point::new(5, 2)));
+        assert!(!r.contains(&Point::new(2, -1)));
+    }
+
+    #[test]
+    fn contains_rectangle() {
+        let r = Rectangle::new(Point::new(4, 4), Point::new(0, 0));
+        assert!(r.contains_rectangle(&square(Point::new(1, 1), 2)));
+        assert!(r.contains_rectangle(&r));
+        assert!(!r.contains_rectangle(&square(Point::new(3, 3), 2)));
+    }
+
+    #[test]
+    fn intersection() {
+        let a = Rectangle::new(Point::new(0, 0), Point::new(4, 4));
+        let b = Rectangle::new(Point::new(6, 2), Point::new(2, 6));
+        assert_eq!(a.intersection(&b), Some(Rectangle::new(Point::new(2, 2), Point::new(4, 4))));
+        assert_eq!(a.intersection(&b), b.intersection(&a));
+
+        // touching rectangles share an edge
+        let c = square(Point::new(4, 0), 4);
+        assert_eq!(a.intersection(&c).map(|r| (r.width(), r.height())), Some((0, 4)));
+
+        let d = square(Point::new(5, 5), 1);
+        assert_eq!(a.intersection(&d), None);
+    }
+
+    #[test]
+    fn union() {
+        let a = square(Point::new(0.0, 0.0), 1.0);
+        let b = square(Point::new(2.0, -1.0), 1.0);
+        let u = a.union(&b);
+        assert_eq!(u, Rectangle::new(Point::new(0.0, -1.0), Point::new(3.0, 1.0)));
+        assert!(u.contains_rectangle(&a) && u.contains_rectangle(&b));
+        assert_eq!(u, b.union(&a));
+    }
+
+    #[test]
+    fn signed_area() {
+        let (p1, p2) = (Point::new(0, 0), Point::new(3, 4));
+        assert_eq!(Rectangle::signed_area(p1, p2), 12);
+        assert_eq!(Rectangle::signed_area(p2, p1), 12);
+        assert_eq!(Rectangle::signed_area(Point::new(3, 0), Point::new(0, 4)), -12);
+    }
+}
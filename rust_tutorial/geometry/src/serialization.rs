@@ -0,0 +1,28 @@
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Serialize, Serializer};
+
+use crate::point::{Coordinate, Point};
+use crate::rectangle::Rectangle;
+
+// A rectangle is written as its normalized corners. Read back, the corners go through
+// `Rectangle::try_new`, so that a rectangle from untrusted input is normalized and has no NaN or
+// infinite coordinate, like any other.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "Rectangle")]
+struct Corners<T> {
+    min: Point<T>,
+    max: Point<T>,
+}
+
+impl<T: Coordinate + Serialize> Serialize for Rectangle<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Corners { min: self.min(), max: self.max() }.serialize(serializer)
+    }
+}
+
+impl<'de, T: Coordinate + Deserialize<'de>> Deserialize<'de> for Rectangle<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let Corners { min, max } = Corners::deserialize(deserializer)?;
+        Rectangle::try_new(min, max).map_err(D::Error::custom)
+    }
+}
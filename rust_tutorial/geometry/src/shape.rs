@@ -0,0 +1,427 @@
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fmt::{self, Display};
+
+use crate::point::{max, min, Coordinate, CoordinateError, Point};
+use crate::rectangle::Rectangle;
+
+// Shapes measure in `f64`, the type a circle's area needs.
+pub trait HasArea {
+    fn area(&self) -> f64;
+}
+
+pub trait HasPerimeter {
+    fn perimeter(&self) -> f64;
+}
+
+pub trait BoundingBox {
+    // the smallest rectangle, with sides parallel to the axes, containing the shape
+    fn bounding_box(&self) -> Rectangle<f64>;
+}
+
+pub trait Contains {
+    // points on the boundary are inside
+    fn contains(&self, p: &Point<f64>) -> bool;
+}
+
+// All of the above, so that shapes of different types can be kept together as `Box<dyn Shape>`.
+// Implemented for every type that has them all.
+pub trait Shape: HasArea + HasPerimeter + BoundingBox + Contains {}
+
+impl<T: HasArea + HasPerimeter + BoundingBox + Contains> Shape for T {}
+
+// The fields of the shapes are private, like those of `Rectangle`, so that every shape went
+// through the checks of its `new`: its coordinates are finite, and so is its bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    center: Point<f64>,
+    radius: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle {
+    a: Point<f64>,
+    b: Point<f64>,
+    c: Point<f64>,
+}
+
+// A simple polygon, i.e. one whose edges only meet at their shared vertices, given by its
+// vertices in order, clockwise or not. The last vertex is joined to the first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    vertices: Vec<Point<f64>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShapeError {
+    // a NaN or infinite coordinate, given or of the bounding box
+    Coordinate(CoordinateError),
+    // a negative, NaN or infinite radius
+    Radius(f64),
+    // the number of vertices of a polygon with fewer than 3
+    Vertices(usize),
+}
+
+impl Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShapeError::Coordinate(error) => write!(f, "{}", error),
+            ShapeError::Radius(radius) => write!(f, "radius {} is not a finite number at least 0", radius),
+            ShapeError::Vertices(count) => write!(f, "a polygon needs at least 3 vertices, found {}", count),
+        }
+    }
+}
+
+impl Error for ShapeError {}
+
+impl From<CoordinateError> for ShapeError {
+    fn from(error: CoordinateError) -> ShapeError {
+        ShapeError::Coordinate(error)
+    }
+}
+
+fn validate(p: &Point<f64>) -> Result<(), CoordinateError> {
+    p.x.validate()?;
+    p.y.validate()?;
+    Ok(())
+}
+
+impl Circle {
+    pub fn new(center: Point<f64>, radius: f64) -> Result<Circle, ShapeError> {
+        validate(&center)?;
+        // written so that NaN fails too
+        if !(radius >= 0.0 && radius.is_finite()) {
+            return Err(ShapeError::Radius(radius));
+        }
+        let circle = Circle { center, radius };
+        // a large center and radius can still overflow
+        circle.try_bounding_box()?;
+        Ok(circle)
+    }
+
+    pub fn center(&self) -> Point<f64> {
+        self.center
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn try_bounding_box(&self) -> Result<Rectangle<f64>, CoordinateError> {
+        let Point { x, y } = self.center;
+        let r = self.radius;
+        Rectangle::try_new(Point::new(x - r, y - r), Point::new(x + r, y + r))
+    }
+}
+
+impl Triangle {
+    pub fn new(a: Point<f64>, b: Point<f64>, c: Point<f64>) -> Result<Triangle, ShapeError> {
+        for p in [a, b, c].iter() {
+            validate(p)?;
+        }
+        Ok(Triangle { a, b, c })
+    }
+
+    pub fn vertices(&self) -> [Point<f64>; 3] {
+        [self.a, self.b, self.c]
+    }
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Point<f64>>) -> Result<Polygon, ShapeError> {
+        if vertices.len() < 3 {
+            return Err(ShapeError::Vertices(vertices.len()));
+        }
+        for p in vertices.iter() {
+            validate(p)?;
+        }
+        Ok(Polygon { vertices })
+    }
+
+    pub fn vertices(&self) -> &[Point<f64>] {
+        &self.vertices
+    }
+}
+
+impl HasArea for Rectangle<f64> {
+    fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+}
+
+impl HasPerimeter for Rectangle<f64> {
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.width() + self.height())
+    }
+}
+
+impl BoundingBox for Rectangle<f64> {
+    fn bounding_box(&self) -> Rectangle<f64> {
+        *self
+    }
+}
+
+impl Contains for Rectangle<f64> {
+    fn contains(&self, p: &Point<f64>) -> bool {
+        Rectangle::contains(self, p)
+    }
+}
+
+impl HasArea for Circle {
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+}
+
+impl HasPerimeter for Circle {
+    fn perimeter(&self) -> f64 {
+        2.0 * PI * self.radius
+    }
+}
+
+impl BoundingBox for Circle {
+    fn bounding_box(&self) -> Rectangle<f64> {
+        // checked by `new`
+        self.try_bounding_box().unwrap()
+    }
+}
+
+impl Contains for Circle {
+    fn contains(&self, p: &Point<f64>) -> bool {
+        distance(&self.center, p) <= self.radius
+    }
+}
+
+// A triangle is the simplest polygon; it keeps its vertices inline rather than in a `Vec`.
+impl HasArea for Triangle {
+    fn area(&self) -> f64 {
+        shoelace(&[self.a, self.b, self.c])
+    }
+}
+
+impl HasPerimeter for Triangle {
+    fn perimeter(&self) -> f64 {
+        perimeter(&[self.a, self.b, self.c])
+    }
+}
+
+impl BoundingBox for Triangle {
+    fn bounding_box(&self) -> Rectangle<f64> {
+        bounding_box(&[self.a, self.b, self.c])
+    }
+}
+
+impl Contains for Triangle {
+    fn contains(&self, p: &Point<f64>) -> bool {
+        contains(&[self.a, self.b, self.c], p)
+    }
+}
+
+impl HasArea for Polygon {
+    fn area(&self) -> f64 {
+        shoelace(&self.vertices)
+    }
+}
+
+impl HasPerimeter for Polygon {
+    fn perimeter(&self) -> f64 {
+        perimeter(&self.vertices)
+    }
+}
+
+impl BoundingBox for Polygon {
+    fn bounding_box(&self) -> Rectangle<f64> {
+        bounding_box(&self.vertices)
+    }
+}
+
+impl Contains for Polygon {
+    fn contains(&self, p: &Point<f64>) -> bool {
+        contains(&self.vertices, p)
+    }
+}
+
+fn distance(a: &Point<f64>, b: &Point<f64>) -> f64 {
+    (b.x - a.x).hypot(b.y - a.y)
+}
+
+// the edges of a polygon, as (start, end) pairs, the last one closing it
+fn edges(vertices: &[Point<f64>]) -> impl Iterator<Item = (&Point<f64>, &Point<f64>)> {
+    vertices.iter().zip(vertices.iter().cycle().skip(1))
+}
+
+// The shoelace formula: the sum of the cross products of the edges is twice the signed area,
+// positive when the vertices go counterclockwise.
+fn shoelace(vertices: &[Point<f64>]) -> f64 {
+    let twice = edges(vertices).map(|(a, b)| a.x * b.y - b.x * a.y).sum::<f64>();
+    twice.abs() / 2.0
+}
+
+fn perimeter(vertices: &[Point<f64>]) -> f64 {
+    edges(vertices).map(|(a, b)| distance(a, b)).sum()
+}
+
+fn bounding_box(vertices: &[Point<f64>]) -> Rectangle<f64> {
+    let first = vertices[0];
+    vertices.iter().fold(Rectangle::new(first, first), |bounds, p| {
+        let (lo, hi) = (bounds.min(), bounds.max());
+        Rectangle::new(
+            Point::new(min(lo.x, p.x), min(lo.y, p.y)),
+            Point::new(max(hi.x, p.x), max(hi.y, p.y)),
+        )
+    })
+}
+
+// Ray casting: a point is inside when a ray from it crosses the edges an odd number of times.
+// The ray goes right; an edge counts when the point is at the height of one of its ends but not
+// the other, so a ray through a vertex counts it once. Points on an edge are checked first, as
+// the count is not reliable for them.
+fn contains(vertices: &[Point<f64>], p: &Point<f64>) -> bool {
+    if edges(vertices).any(|(a, b)| on_segment(a, b, p)) {
+        return true;
+    }
+    let mut inside = false;
+    for (a, b) in edges(vertices) {
+        if (a.y > p.y) != (b.y > p.y) {
+            let x = a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y);
+            if p.x < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+fn on_segment(a: &Point<f64>, b: &Point<f64>, p: &Point<f64>) -> bool {
+    let cross = (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+    // relative to the length of the edge, so that the tolerance does not depend on the scale
+    let scale = distance(a, b).max(f64::MIN_POSITIVE);
+    (cross / scale).abs() <= 1e-9 * scale && bounding_box(&[*a, *b]).contains(p)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::{BoundingBox, Circle, Contains, HasArea, HasPerimeter, Polygon, Shape, ShapeError, Triangle};
+    use crate::point::{CoordinateError, Point};
+    use crate::rectangle::Rectangle;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn rectangles() {
+        let r = Rectangle::new(Point::new(3.0, 4.0), Point::origin());
+        assert!(close(HasArea::area(&r), 12.0));
+        assert!(close(HasPerimeter::perimeter(&r), 14.0));
+        assert_eq!(r.bounding_box(), Rectangle::new(Point::origin(), Point::new(3.0, 4.0)));
+        assert!(Contains::contains(&r, &Point::new(3.0, 2.0)));
+        assert!(!Contains::contains(&r, &Point::new(3.5, 2.0)));
+    }
+
+    #[test]
+    fn circles() {
+        let c = Circle::new(Point::new(1.0, 1.0), 2.0).unwrap();
+        assert!(close(c.area(), 4.0 * PI));
+        assert!(close(c.perimeter(), 4.0 * PI));
+        assert_eq!(c.bounding_box(), Rectangle::new(Point::new(-1.0, -1.0), Point::new(3.0, 3.0)));
+        assert!(c.contains(&Point::new(1.0, 3.0)));
+        // inside the bounding box, but not the circle
+        assert!(!c.contains(&Point::new(2.9, 2.9)));
+    }
+
+    #[test]
+    fn triangles() {
+        let t = Triangle::new(Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(0.0, 3.0)).unwrap();
+        assert!(close(t.area(), 6.0));
+        assert!(close(t.perimeter(), 12.0));
+        assert_eq!(t.bounding_box(), Rectangle::new(Point::origin(), Point::new(4.0, 3.0)));
+        assert!(t.contains(&Point::new(1.0, 1.0)));
+        // on the hypotenuse
+        assert!(t.contains(&Point::new(2.0, 1.5)));
+        assert!(!t.contains(&Point::new(3.0, 2.0)));
+
+        // the order of the vertices does not matter
+        let [a, b, c] = t.vertices();
+        let clockwise = Triangle::new(a, c, b).unwrap();
+        assert!(close(clockwise.area(), 6.0));
+        assert!(clockwise.contains(&Point::new(1.0, 1.0)));
+    }
+
+    #[test]
+    fn polygons() {
+        // an L shape, not convex
+        let l = Polygon::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(4.0, 0.0),
+            Point::new(4.0, 1.0),
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 3.0),
+            Point::new(0.0, 3.0),
+        ]).unwrap();
+        assert!(close(l.area(), 6.0));
+        assert!(close(l.perimeter(), 14.0));
+        assert_eq!(l.bounding_box(), Rectangle::new(Point::origin(), Point::new(4.0, 3.0)));
+        assert!(l.contains(&Point::new(0.5, 2.5)));
+        assert!(l.contains(&Point::new(3.5, 0.5)));
+        assert!(l.contains(&Point::new(1.0, 2.0)));
+        // in the notch
+        assert!(!l.contains(&Point::new(2.0, 2.0)));
+        // at the height of a vertex, right of the shape
+        assert!(!l.contains(&Point::new(5.0, 1.0)));
+
+        // a square given as a polygon measures as the rectangle does
+        let square = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(0.0, 2.0), Point::new(2.0, 2.0), Point::new(2.0, 0.0)]).unwrap();
+        let rectangle = Rectangle::new(Point::origin(), Point::new(2.0, 2.0));
+        assert!(close(square.area(), HasArea::area(&rectangle)));
+        assert!(close(square.perimeter(), HasPerimeter::perimeter(&rectangle)));
+    }
+
+    #[test]
+    fn mixed() {
+        let shapes: Vec<Box<dyn Shape>> = vec![
+            Box::new(Rectangle::new(Point::origin(), Point::new(2.0, 1.0))),
+            Box::new(Circle::new(Point::origin(), 1.0).unwrap()),
+            Box::new(Triangle::new(Point::new(0.0, 0.0), Point::new(2.0, 0.0), Point::new(0.0, 2.0)).unwrap()),
+            Box::new(Polygon::new(vec![Point::new(-1.0, -1.0), Point::new(1.0, -1.0), Point::new(0.0, 1.0)]).unwrap()),
+        ];
+        let total = shapes.iter().map(|shape| shape.area()).sum::<f64>();
+        assert!(close(total, 2.0 + PI + 2.0 + 2.0));
+
+        let p = Point::new(0.2, 0.2);
+        assert_eq!(shapes.iter().filter(|shape| shape.contains(&p)).count(), 4);
+        let bounds = shapes.iter().map(|shape| shape.bounding_box()).fold(None, |bounds: Option<Rectangle<f64>>, b| {
+            Some(bounds.map_or(b, |bounds| bounds.union(&b)))
+        });
+        assert_eq!(bounds, Some(Rectangle::new(Point::new(-1.0, -1.0), Point::new(2.0, 2.0))));
+    }
+
+    #[test]
+    fn invalid_circles() {
+        let origin = Point::origin();
+        assert_eq!(Circle::new(origin, -1.0), Err(ShapeError::Radius(-1.0)));
+        assert!(matches!(Circle::new(origin, f64::NAN), Err(ShapeError::Radius(r)) if r.is_nan()));
+        assert_eq!(Circle::new(origin, f64::INFINITY), Err(ShapeError::Radius(f64::INFINITY)));
+        assert_eq!(Circle::new(Point::new(f64::NAN, 0.0), 1.0), Err(ShapeError::Coordinate(CoordinateError::NaN)));
+        // the bounding box would overflow
+        assert_eq!(Circle::new(Point::new(f64::MAX, 0.0), f64::MAX), Err(ShapeError::Coordinate(CoordinateError::Infinite)));
+        assert_eq!(Circle::new(origin, 0.0).map(|c| c.area()), Ok(0.0));
+    }
+
+    #[test]
+    fn invalid_triangles() {
+        let origin = Point::origin();
+        let far = Point::new(0.0, f64::NEG_INFINITY);
+        assert_eq!(Triangle::new(origin, far, origin), Err(ShapeError::Coordinate(CoordinateError::Infinite)));
+    }
+
+    #[test]
+    fn invalid_polygons() {
+        assert_eq!(Polygon::new(Vec::new()), Err(ShapeError::Vertices(0)));
+        assert_eq!(Polygon::new(vec![Point::origin(), Point::new(1.0, 1.0)]), Err(ShapeError::Vertices(2)));
+        let vertices = vec![Point::origin(), Point::new(1.0, 0.0), Point::new(f64::NAN, 1.0)];
+        assert_eq!(Polygon::new(vertices), Err(ShapeError::Coordinate(CoordinateError::NaN)));
+    }
+}
@@ -1,20 +1,14 @@
 use std::fmt::{Formatter, Display, Result};
 
-struct City {
-    name: &'static str,
-    lat: f32,
-    lon: f32,
-}
-
-impl Display for City {
-    fn fmt(&self, f: &mut Formatter) -> Result {
-        let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
-        let lon_c = if self.lon >= 0.0 { 'E' } else { 'W' };
+// `Person`, `Color` and `City` live in the records crate (see records/), `Person` and `Color`
+// shared with custom_types.rs
+//
+// $ (cd records && cargo build)
+// $ rustc helloworld.rs --extern records=records/target/debug/librecords.rlib \
+//     -L dependency=records/target/debug/deps
+extern crate records;
 
-        write!(f, "{}: {:.3}°{} {:.3}°{}",
-            self.name, self.lat.abs(), lat_c, self.lon.abs(), lon_c)
-    }
-}
+use records::{City, Color, Person};
 
 #[derive(Debug)]
 struct Structure(i32);
@@ -22,12 +16,6 @@ struct Structure(i32);
 #[derive(Debug)]
 struct Deep(Structure);
 
-#[derive(Debug)]
-struct Person<'a> {
-    name: &'a str,
-    age: u8,
-}
-
 impl Display for Structure {
     fn fmt(&self, f: &mut Formatter) -> Result {
         write!(f, "s({})", self.0)
@@ -61,31 +49,13 @@ impl Display for List {
     }
 }
 
-#[derive(Debug)]
-struct Color {
-    red: u8,
-    green: u8,
-    blue: u8,
-}
-
-impl Display for Color {
-    fn fmt(&self, f: &mut Formatter) -> Result {
-        let num =
-            self.red as u32 * 16 * 16 +
-            self.green as u32 * 16 +
-            self.blue as u32;
-        write!(f, "RGB ({}, {}, {}) 0x{:06X}", self.red, self.green, self.blue, num)
-        // {:06X} - complete with 0s for 6 digits and use hexa format with capitals
-    }
-}
-
 fn main() {
     println!("{} {} {obj}", 1, 2, obj="valoare");
 
     println!("{}", Deep(Structure(7))); // use my impl
     println!("{:?}", Deep(Structure(7))); // use debug -> need #[derive(Debug)]
 
-    let name = "Peter";
+    let name = "Peter".to_owned();
     let age = 22;
     let peter = Person { name, age };
     println!("{:#?}", peter); // for pretty
@@ -93,19 +63,24 @@ fn main() {
     let v = List(vec![1, 2, 3]);
     println!("{}", v); // stuff with ! are macros
 
-    for city in [
-        City { name: "Dublin", lat: 53.347778, lon: -6.259722 },
-        City { name: "Oslo", lat: 59.95, lon: 10.75 },
-        City { name: "Vancouver", lat: 49.25, lon: -123.1 },
-    ].iter() {
+    let cities = [
+        City::new("Dublin", 53.347778, -6.259722).unwrap(),
+        City::new("Oslo", 59.95, 10.75).unwrap(),
+        City::new("Vancouver", 49.25, -123.1).unwrap(),
+    ];
+    for city in cities.iter() {
         println!("{}", *city);
     }
+    // and back, to the 3 decimals shown
+    let dublin: City = "Dublin: 53.348°N 6.260°W".parse().unwrap();
+    println!("{:.0} km to {}", dublin.distance_km(&cities[1]), cities[1].name());
 
     for color in [
        Color { red: 128, green: 255, blue: 90 },
        Color { red: 0, green: 3, blue: 254 },
        Color { red: 0, green: 0, blue: 0 },
    ].iter() {
-       println!("{}", *color);
+       // {:X} - `Color` implements `UpperHex` as its 6 hex digits, in capitals
+       println!("RGB ({}, {}, {}) 0x{:X}", color.red, color.green, color.blue, *color);
    }
 }
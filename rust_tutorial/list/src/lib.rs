@@ -0,0 +1,306 @@
+// The linked list of custom_types.rs, made generic and persistent: a list is never changed once
+// built, so adding an element makes a new list whose tail is the old one, shared rather than
+// copied. Sharing is counted (`Rc`): a node lives as long as some list still reaches it.
+//
+// Lists can be long, so nothing here recurses over the nodes: `len`, iteration, and also
+// dropping, which for nested boxes would recurse once per node and overflow the stack.
+use std::fmt;
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+pub struct List<T> {
+    head: Link<T>,
+}
+
+type Link<T> = Option<Rc<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+}
+
+impl<T> List<T> {
+    pub fn new() -> List<T> {
+        List { head: None }
+    }
+
+    // a list starting with `elem`, followed by this one
+    pub fn add(&self, elem: T) -> List<T> {
+        List { head: Some(Rc::new(Node { elem, next: self.head.clone() })) }
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    // the list without its first element; empty if this one is
+    pub fn tail(&self) -> List<T> {
+        List { head: self.head.as_ref().and_then(|node| node.next.clone()) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    // walks the list, in a loop rather than through recursion
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: self.head.as_deref() }
+    }
+
+    pub fn fold<B, F: FnMut(B, &T) -> B>(&self, init: B, f: F) -> B {
+        self.iter().fold(init, f)
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> List<U> {
+        self.iter().map(f).collect()
+    }
+
+    // shares nothing with this list, as every node of it changes
+    pub fn reverse(&self) -> List<T> where T: Clone {
+        self.fold(List::new(), |reversed, elem| reversed.add(elem.clone()))
+    }
+}
+
+// A copy sharing all the nodes
+impl<T> Clone for List<T> {
+    fn clone(&self) -> List<T> {
+        List { head: self.head.clone() }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> List<T> {
+        List::new()
+    }
+}
+
+// Dropping the nodes one after the other, while they are not shared; the default drop of the
+// head would drop its `next`, which would drop its own `next`, and so on.
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        let mut next = self.head.take();
+        while let Some(node) = next {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => next = node.next.take(),
+                // another list still uses the rest
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+// Elements are moved out of the nodes this list alone uses, and cloned from shared ones.
+pub struct IntoIter<T> {
+    list: List<T>,
+}
+
+impl<T: Clone> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let node = self.list.head.take()?;
+        match Rc::try_unwrap(node) {
+            Ok(mut node) => {
+                self.list.head = node.next.take();
+                Some(node.elem)
+            },
+            Err(node) => {
+                self.list.head = node.next.clone();
+                Some(node.elem.clone())
+            },
+        }
+    }
+}
+
+impl<T: Clone> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+// The first element of the iterator is the head of the list
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> List<T> {
+        let elems = iter.into_iter().collect::<Vec<_>>();
+        elems.into_iter().rev().fold(List::new(), |list, elem| list.add(elem))
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &List<T>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+// [1, 2, 3]
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        for (index, elem) in self.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", elem)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::List;
+
+    // long enough for a recursive drop, or walk, to overflow the stack of a test thread
+    const LONG: u64 = 1_000_000;
+
+    #[test]
+    fn add_head_and_tail() {
+        let empty = List::<u32>::new();
+        assert!(empty.is_empty());
+        assert_eq!(empty.len(), 0);
+        assert_eq!(empty.head(), None);
+        assert!(empty.tail().is_empty());
+
+        let list = empty.add(3).add(2).add(1);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.head(), Some(&1));
+        assert_eq!(list.tail().head(), Some(&2));
+        assert_eq!(list.tail().tail().tail(), empty);
+        // the old list is still there
+        assert!(empty.is_empty());
+        assert_eq!(list, vec![1, 2, 3].into_iter().collect());
+    }
+
+    #[test]
+    fn tails_are_shared() {
+        let tail = (2..5).collect::<List<_>>();
+        let a = tail.add(1);
+        let b = tail.add(10);
+        assert_eq!(a.to_string(), "[1, 2, 3, 4]");
+        assert_eq!(b.to_string(), "[10, 2, 3, 4]");
+        assert!(std::ptr::eq(a.tail().head().unwrap(), b.tail().head().unwrap()));
+        assert!(std::ptr::eq(a.clone().head().unwrap(), a.head().unwrap()));
+    }
+
+    #[test]
+    fn dropping_keeps_the_nodes_other_lists_use() {
+        let elem = Rc::new(5);
+        let shared = List::new().add(elem.clone());
+        let longer = shared.add(Rc::new(6));
+        drop(shared);
+        assert_eq!(Rc::strong_count(&elem), 2);
+        assert_eq!(longer.tail().head(), Some(&elem));
+        drop(longer);
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn iterating() {
+        let list = (1..=4).collect::<List<u64>>();
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        let mut sum = 0;
+        for x in &list {
+            sum += x;
+        }
+        assert_eq!(sum, 10);
+
+        // by value: moved out of unshared nodes, cloned from shared ones
+        let shared = list.tail();
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(shared.into_iter().map(|x| x * 10).collect::<Vec<_>>(), vec![20, 30, 40]);
+
+        let strings = vec!["a".to_owned(), "b".to_owned()].into_iter().collect::<List<_>>();
+        assert_eq!(strings.into_iter().collect::<String>(), "ab");
+    }
+
+    #[test]
+    fn transforming() {
+        let list = (1..=4).collect::<List<i32>>();
+        assert_eq!(list.reverse().to_string(), "[4, 3, 2, 1]");
+        assert_eq!(list.reverse().reverse(), list);
+        assert_eq!(list.map(|x| x * x).to_string(), "[1, 4, 9, 16]");
+        assert_eq!(list.map(|x| x.to_string()).fold(String::new(), |s, x| s + x), "1234");
+        assert_eq!(list.fold(0, |sum, x| sum + x), 10);
+        assert_eq!(List::<i32>::new().reverse(), List::new());
+        assert_eq!(List::<i32>::new().to_string(), "[]");
+        assert_eq!(format!("{:?}", list), "[1, 2, 3, 4]");
+    }
+
+    #[test]
+    fn long_lists() {
+        let list = (0..LONG).collect::<List<u64>>();
+        assert_eq!(list.len(), LONG as usize);
+        assert_eq!(list.fold(0, |sum, x| sum + x), LONG * (LONG - 1) / 2);
+        let reversed = list.reverse();
+        assert_eq!(reversed.head(), Some(&(LONG - 1)));
+        let doubled = list.map(|x| x * 2);
+        assert_eq!(doubled.iter().last(), Some(&(2 * (LONG - 1))));
+        assert_eq!(list.clone().into_iter().count(), LONG as usize);
+        assert_eq!(list, list.clone());
+        assert_eq!(list.to_string().len(), list.iter().map(|x| x.to_string().len() + 2).sum::<usize>());
+    }
+
+    #[test]
+    fn dropping_a_long_list() {
+        // every node holds the same `Rc`, so its count tells how many nodes are alive
+        let elem = Rc::new(());
+        let list = (0..LONG).map(|_| elem.clone()).collect::<List<_>>();
+        assert_eq!(Rc::strong_count(&elem), LONG as usize + 1);
+        drop(list);
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn dropping_a_long_list_with_a_shared_tail() {
+        let elem = Rc::new(());
+        let list = (0..LONG).map(|_| elem.clone()).collect::<List<_>>();
+        // halfway through, the rest of the list is shared with `half`
+        let half = list.iter().take(LONG as usize / 2).fold(list.clone(), |l, _| l.tail());
+        drop(list);
+        assert_eq!(half.len(), LONG as usize / 2);
+        assert_eq!(Rc::strong_count(&elem), LONG as usize / 2 + 1);
+        drop(half);
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+}
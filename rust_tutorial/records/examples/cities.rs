@@ -0,0 +1,22 @@
+// Prints the cities nearest to one from a CSV file (by default examples/cities.csv):
+// cargo run --example cities [csv] [city]
+use std::env;
+use std::fs;
+
+use records::geo::parse_csv;
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| "examples/cities.csv".to_owned());
+    let name = args.next().unwrap_or_else(|| "Dublin".to_owned());
+    let csv = fs::read_to_string(&path).unwrap_or_else(|error| panic!("cannot read {}: {}", path, error));
+    let cities = parse_csv(&csv).unwrap_or_else(|error| panic!("{}: {}", path, error));
+    let from = match cities.iter().find(|city| city.name() == name) {
+        Some(city) => city,
+        None => panic!("{} is not in {}", name, path),
+    };
+    println!("{}", from);
+    for (distance, city) in from.nearest(&cities, 5) {
+        println!("  {:>7.0} km, bearing {:>3.0}°: {}", distance, from.bearing(city), city);
+    }
+}
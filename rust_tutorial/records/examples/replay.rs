@@ -0,0 +1,34 @@
+// Replays a recorded event log, and writes it back in the form `format_log` gives:
+// cargo run --example replay [log]
+use std::env;
+use std::fs;
+
+use records::{format_log, parse_log, WebEvent};
+
+fn inspect(event: &WebEvent) {
+    match event {
+        WebEvent::PageLoad => println!("page loaded"),
+        WebEvent::PageUnload => println!("page unloaded"),
+        WebEvent::KeyPress(c) => println!("pressed '{}'", c),
+        WebEvent::Paste(s) => println!("pasted \"{}\"", s),
+        WebEvent::Click { x, y } => println!("clicked at x={}, y={}", x, y),
+    }
+}
+
+fn main() {
+    let path = env::args().nth(1).unwrap_or_else(|| "examples/events.log".to_owned());
+    let log = fs::read_to_string(&path).unwrap_or_else(|error| panic!("cannot read {}: {}", path, error));
+    let events = match parse_log(&log) {
+        Ok(events) => events,
+        Err(error) => {
+            eprintln!("{}: {}", path, error);
+            std::process::exit(1);
+        },
+    };
+    for event in events.iter() {
+        inspect(event);
+    }
+
+    println!("{} events replayed, written back as:", events.len());
+    print!("{}", format_log(&events));
+}
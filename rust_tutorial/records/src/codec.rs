@@ -0,0 +1,161 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+use bincode::Options;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+// Records are exchanged either as JSON, for people and other programs, or in a compact binary
+// format, between our tools:
+//   - integers are variable length: up to 250 in one byte, larger ones in a marker byte and the
+//     little endian bytes of the value; signed integers are zigzag encoded first, so that small
+//     negative values are small too
+//   - floats are their 4 or 8 little endian bytes, and chars their UTF-8 bytes
+//   - strings and sequences are prefixed by their length, as such an integer
+//   - the variants of an enum are prefixed by their index, as such an integer
+//   - struct fields follow each other in declaration order, without names
+// The binary format has no field names or types, so both sides must agree on the record type.
+//
+// Malformed input is reported rather than guessed at: missing or unknown fields, values of the
+// wrong type, invalid values (e.g. a rectangle with a NaN corner), truncated input, and anything
+// left after the record.
+
+// the largest binary record read or written
+const MAX_RECORD: u64 = 1 << 20;
+
+#[derive(Debug)]
+pub enum Error {
+    Json(serde_json::Error),
+    Binary(bincode::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            // includes the line and column of the error
+            Error::Json(error) => write!(f, "malformed JSON record: {}", error),
+            Error::Binary(error) => match **error {
+                // also when a length prefix is larger than what is left of the input
+                bincode::ErrorKind::Io(ref error) if error.kind() == io::ErrorKind::UnexpectedEof => {
+                    write!(f, "malformed binary record: the input ends inside the record")
+                },
+                _ => write!(f, "malformed binary record: {}", error),
+            },
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Json(error) => Some(error),
+            Error::Binary(error) => Some(error),
+        }
+    }
+}
+
+fn binary() -> impl Options {
+    // varint integers, little endian, no trailing bytes
+    bincode::DefaultOptions::new().with_limit(MAX_RECORD)
+}
+
+pub fn to_json<T: Serialize>(record: &T) -> String {
+    // only maps with non-string keys fail, and records have none
+    serde_json::to_string(record).expect("records serialize to JSON")
+}
+
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, Error> {
+    serde_json::from_str(json).map_err(Error::Json)
+}
+
+pub fn to_binary<T: Serialize>(record: &T) -> Result<Vec<u8>, Error> {
+    binary().serialize(record).map_err(Error::Binary)
+}
+
+pub fn from_binary<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Error> {
+    binary().deserialize(bytes).map_err(Error::Binary)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+
+    use super::{from_binary, from_json, to_binary, to_json, Error};
+    use crate::{City, Color, Person, Point, Rectangle, WebEvent};
+
+    fn roundtrip<T: Serialize + DeserializeOwned + PartialEq + Debug>(record: T) {
+        let json = to_json(&record);
+        assert_eq!(from_json::<T>(&json).unwrap(), record, "{}", json);
+        let bytes = to_binary(&record).unwrap();
+        assert_eq!(from_binary::<T>(&bytes).unwrap(), record, "{:?}", bytes);
+    }
+
+    // the error, which must mention `expected`
+    fn malformed<T: Debug>(result: Result<T, Error>, expected: &str) {
+        let error = result.expect_err("malformed input was accepted").to_string();
+        assert!(error.contains(expected), "`{}` does not mention `{}`", error, expected);
+    }
+
+    #[test]
+    fn records_read_back_as_written() {
+        roundtrip(Person { name: "Peter".to_owned(), age: 27 });
+        roundtrip(Person { name: String::new(), age: 255 });
+        roundtrip(Point::new(0.3f32, 0.4));
+        roundtrip(Point::new(-7i64, i64::MAX));
+        roundtrip(Rectangle::new(Point::new(3.0, 0.0), Point::new(0.0, 4.0)));
+        roundtrip(Rectangle::new(Point::new(1u32, 2), Point::new(1, 2)));
+        roundtrip(WebEvent::PageLoad);
+        roundtrip(WebEvent::PageUnload);
+        roundtrip(WebEvent::KeyPress('x'));
+        roundtrip(WebEvent::KeyPress('é'));
+        roundtrip(WebEvent::Paste("my text, \"quoted\"\n".to_owned()));
+        roundtrip(WebEvent::Click { x: 20, y: -80 });
+        roundtrip(Color { red: 128, green: 255, blue: 90 });
+        roundtrip(City::new("Dublin", 53.347_778, -6.259_722).unwrap());
+        roundtrip(vec![City::new("Oslo", 59.95, 10.75).unwrap(), City::new("Vancouver", 49.25, -123.1).unwrap()]);
+    }
+
+    // small integers take one byte, strings their length and one
+    #[test]
+    fn binary_is_compact() {
+        assert_eq!(to_binary(&Person { name: "Peter".to_owned(), age: 27 }).unwrap(), b"\x05Peter\x1b");
+        assert_eq!(to_binary(&WebEvent::Click { x: 20, y: -80 }).unwrap(), vec![4, 40, 159]);
+    }
+
+    #[test]
+    fn json_rectangles_are_normalized() {
+        let json = r#"{"min":{"x":3.0,"y":0.0},"max":{"x":0.0,"y":4.0}}"#;
+        assert_eq!(from_json::<Rectangle<f64>>(json).unwrap(), Rectangle::new(Point::new(0.0, 0.0), Point::new(3.0, 4.0)));
+    }
+
+    #[test]
+    fn malformed_json() {
+        malformed(from_json::<Person>(r#"{"name": "Peter"}"#), "missing field `age` at line 1 column 17");
+        malformed(from_json::<Person>(r#"{"name": "Peter", "age": 300}"#), "line 1 column 28");
+        malformed(from_json::<Person>("{\"name\": \"Peter\",\n \"age\": 27,}"), "line 2 column 12");
+        malformed(from_json::<WebEvent>(r#"{"Scroll": 3}"#), "unknown variant `Scroll`");
+        malformed(from_json::<WebEvent>(r#"{"Click": {"x": 20}}"#), "missing field `y`");
+        malformed(from_json::<Color>(r#"{"red": 1, "green": 2, "blue": "3"}"#), "invalid type: string \"3\"");
+        malformed(from_json::<Rectangle<f64>>(r#"{"min": {"x": 0, "y": 0}, "max": {"x": 1e999, "y": 1}}"#), "number out of range");
+        malformed(from_json::<City>(r#"{"name": "Nowhere", "lat": 91.0, "lon": 0.0}"#), "latitude 91 is not between -90 and 90");
+    }
+
+    #[test]
+    fn malformed_binary() {
+        let bytes = to_binary(&Person { name: "Peter".to_owned(), age: 27 }).unwrap();
+        malformed(from_binary::<Person>(&bytes[..bytes.len() - 1]), "ends inside the record");
+        malformed(from_binary::<Person>(&[bytes.clone(), vec![0]].concat()), "bytes remaining");
+        // a name of 2 MiB, of which only a byte is there
+        malformed(from_binary::<Person>(&[0xfc, 0x00, 0x00, 0x20, 0x00, b'P']), "ends inside the record");
+        malformed(to_binary(&WebEvent::Paste("x".repeat(2 << 20))), "size limit");
+        malformed(from_binary::<WebEvent>(&[9]), "variant index");
+        malformed(from_binary::<WebEvent>(&[2, 0xff]), "char is not valid");
+        // a rectangle is its two corners, one of which is not a number here
+        let corners = to_binary(&(Point::new(0.0, 0.0), Point::new(f64::NAN, 1.0))).unwrap();
+        malformed(from_binary::<Rectangle<f64>>(&corners), "NaN");
+    }
+}
@@ -0,0 +1,317 @@
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+// An opaque color, one byte per channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+// Hue in degrees, in [0, 360); saturation and lightness in [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    pub hue: f64,
+    pub saturation: f64,
+    pub lightness: f64,
+}
+
+// Hue in degrees, in [0, 360); saturation and value in [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsv {
+    pub hue: f64,
+    pub saturation: f64,
+    pub value: f64,
+}
+
+// the basic named colors of CSS; note that CSS `green` is only half as bright as `lime`
+const NAMED: [(&str, Color); 16] = [
+    ("black", Color::BLACK),
+    ("silver", Color::SILVER),
+    ("gray", Color::GRAY),
+    ("white", Color::WHITE),
+    ("maroon", Color::MAROON),
+    ("red", Color::RED),
+    ("purple", Color::PURPLE),
+    ("fuchsia", Color::FUCHSIA),
+    ("green", Color::GREEN),
+    ("lime", Color::LIME),
+    ("olive", Color::OLIVE),
+    ("yellow", Color::YELLOW),
+    ("navy", Color::NAVY),
+    ("blue", Color::BLUE),
+    ("teal", Color::TEAL),
+    ("aqua", Color::AQUA),
+];
+
+impl Color {
+    pub const BLACK: Color = Color::from_hex(0x000000);
+    pub const SILVER: Color = Color::from_hex(0xc0c0c0);
+    pub const GRAY: Color = Color::from_hex(0x808080);
+    pub const WHITE: Color = Color::from_hex(0xffffff);
+    pub const MAROON: Color = Color::from_hex(0x800000);
+    pub const RED: Color = Color::from_hex(0xff0000);
+    pub const PURPLE: Color = Color::from_hex(0x800080);
+    pub const FUCHSIA: Color = Color::from_hex(0xff00ff);
+    pub const GREEN: Color = Color::from_hex(0x008000);
+    pub const LIME: Color = Color::from_hex(0x00ff00);
+    pub const OLIVE: Color = Color::from_hex(0x808000);
+    pub const YELLOW: Color = Color::from_hex(0xffff00);
+    pub const NAVY: Color = Color::from_hex(0x000080);
+    pub const BLUE: Color = Color::from_hex(0x0000ff);
+    pub const TEAL: Color = Color::from_hex(0x008080);
+    pub const AQUA: Color = Color::from_hex(0x00ffff);
+
+    pub const fn new(red: u8, green: u8, blue: u8) -> Color {
+        Color { red, green, blue }
+    }
+
+    // from `0xRRGGBB`; higher bits are ignored
+    pub const fn from_hex(hex: u32) -> Color {
+        Color::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+    }
+
+    // `0xRRGGBB`: each channel is a byte, i.e. 256 times the channel after it
+    pub fn to_hex(&self) -> u32 {
+        u32::from(self.red) << 16 | u32::from(self.green) << 8 | u32::from(self.blue)
+    }
+
+    pub fn named(name: &str) -> Option<Color> {
+        NAMED.iter().find(|(other, _)| other.eq_ignore_ascii_case(name)).map(|(_, color)| *color)
+    }
+
+    pub fn name(&self) -> Option<&'static str> {
+        NAMED.iter().find(|(_, color)| color == self).map(|(name, _)| *name)
+    }
+
+    // This color, with opacity `alpha` in [0, 1], painted over `background`
+    pub fn blend(&self, background: Color, alpha: f64) -> Color {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let mix = |fg: u8, bg: u8| (alpha * f64::from(fg) + (1.0 - alpha) * f64::from(bg)).round() as u8;
+        Color::new(mix(self.red, background.red), mix(self.green, background.green), mix(self.blue, background.blue))
+    }
+
+    pub fn to_hsl(&self) -> Hsl {
+        let (hue, max, min) = self.hue();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * lightness - 1.0).abs()) };
+        Hsl { hue, saturation, lightness }
+    }
+
+    pub fn from_hsl(hsl: Hsl) -> Color {
+        let chroma = (1.0 - (2.0 * hsl.lightness - 1.0).abs()) * hsl.saturation;
+        Color::from_chroma(hsl.hue, chroma, hsl.lightness - chroma / 2.0)
+    }
+
+    pub fn to_hsv(&self) -> Hsv {
+        let (hue, max, min) = self.hue();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+        Hsv { hue, saturation, value: max }
+    }
+
+    pub fn from_hsv(hsv: Hsv) -> Color {
+        let chroma = hsv.value * hsv.saturation;
+        Color::from_chroma(hsv.hue, chroma, hsv.value - chroma)
+    }
+
+    // the hue shared by HSL and HSV, with the largest and smallest channels in [0, 1]; grays
+    // have no hue, and are given 0
+    fn hue(&self) -> (f64, f64, f64) {
+        let [r, g, b] = [self.red, self.green, self.blue].map(|c| f64::from(c) / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let sector = if chroma == 0.0 {
+            0.0
+        } else if max == r {
+            ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / chroma + 2.0
+        } else {
+            (r - g) / chroma + 4.0
+        };
+        (sector * 60.0, max, min)
+    }
+
+    // the color of `hue`, with `chroma` between its largest and smallest channels, and `offset`
+    // added to all of them
+    fn from_chroma(hue: f64, chroma: f64, offset: f64) -> Color {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let channel = |c: f64| ((c + offset).clamp(0.0, 1.0) * 255.0).round() as u8;
+        Color::new(channel(r), channel(g), channel(b))
+    }
+}
+
+// `#rrggbb`, which `FromStr` reads back
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:06x}", self.to_hex())
+    }
+}
+
+// `RRGGBB`, e.g. for `0x{:X}`
+impl fmt::UpperHex for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:06X}", self.to_hex())
+    }
+}
+
+impl fmt::LowerHex for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:06x}", self.to_hex())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseColorError {
+    pub message: String,
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl error::Error for ParseColorError {}
+
+// `#rrggbb`, `rgb(r, g, b)` with decimal channels in [0, 255], or a name, ignoring case and the
+// spaces around
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(text: &str) -> Result<Color, ParseColorError> {
+        let text = text.trim();
+        let error = |message: String| Err(ParseColorError { message });
+        if let Some(hex) = text.strip_prefix('#') {
+            if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return error(format!("invalid color `{}`, expected `#` and 6 hex digits", text));
+            }
+            return Ok(Color::from_hex(u32::from_str_radix(hex, 16).unwrap()));
+        }
+
+        let lower = text.to_ascii_lowercase();
+        if let Some(args) = lower.strip_prefix("rgb") {
+            let args = match args.trim_start().strip_prefix('(').and_then(|args| args.strip_suffix(')')) {
+                Some(args) => args,
+                None => return error(format!("invalid color `{}`, expected `rgb(red, green, blue)`", text)),
+            };
+            // `parse` alone would also take a `+` sign
+            let channel = |c: &str| {
+                let c = c.trim();
+                if c.starts_with(|d: char| d.is_ascii_digit()) { c.parse::<u8>().ok() } else { None }
+            };
+            let channels = args.split(',').map(channel).collect::<Vec<_>>();
+            return match channels[..] {
+                [Some(red), Some(green), Some(blue)] => Ok(Color::new(red, green, blue)),
+                _ => error(format!("invalid color `{}`, expected 3 channels from 0 to 255", text)),
+            };
+        }
+
+        Color::named(&lower).map_or_else(|| error(format!("unknown color `{}`", text)), Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Color, Hsl, Hsv};
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn hex() {
+        // each channel is worth 256 times the next one, not 16
+        assert_eq!(Color::new(128, 255, 90).to_hex(), 0x80ff5a);
+        assert_eq!(Color::new(0, 3, 254).to_hex(), 0x0003fe);
+        assert_eq!(Color::new(1, 0, 0).to_hex(), 0x010000);
+        assert_eq!(Color::from_hex(0x80ff5a), Color::new(128, 255, 90));
+
+        assert_eq!(Color::new(128, 255, 90).to_string(), "#80ff5a");
+        assert_eq!(Color::new(0, 3, 254).to_string(), "#0003fe");
+        assert_eq!(Color::BLACK.to_string(), "#000000");
+        assert_eq!(format!("0x{:X}", Color::new(0, 3, 254)), "0x0003FE");
+        assert_eq!(format!("{:x}", Color::WHITE), "ffffff");
+
+        for hex in (0..=0xffffff).step_by(4099) {
+            let color = Color::from_hex(hex);
+            assert_eq!(color.to_hex(), hex);
+            assert_eq!(color.to_string().parse::<Color>().unwrap(), color);
+        }
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!("#80FF5a".parse::<Color>().unwrap(), Color::new(128, 255, 90));
+        assert_eq!(" rgb(0, 3, 254) ".parse::<Color>().unwrap(), Color::new(0, 3, 254));
+        assert_eq!("RGB(0,0,0)".parse::<Color>().unwrap(), Color::BLACK);
+        assert_eq!("Red".parse::<Color>().unwrap(), Color::RED);
+        assert_eq!("lime".parse::<Color>().unwrap(), Color::from_hex(0x00ff00));
+        assert_eq!(Color::named("navy"), Some(Color::NAVY));
+        assert_eq!(Color::TEAL.name(), Some("teal"));
+        assert_eq!(Color::new(1, 2, 3).name(), None);
+
+        let rejected = [
+            "#80ff5", "#80ff5a0", "#80fg5a", "80ff5a", "rgb(0, 3)", "rgb(0, 3, 256)", "rgb(0, 3, -1)", "rgb(+1, 2, 3)",
+            "rgb(0, 3, 4,)", "rgb(0, , 4)", "rgb 0, 3, 4", "rgba(0, 0, 0, 0)", "reddish", "",
+        ];
+        for text in rejected.iter() {
+            assert!(text.parse::<Color>().is_err(), "`{}` was accepted", text);
+        }
+        assert_eq!("rgb(+1, 2, 3)".parse::<Color>().unwrap_err().to_string(), "invalid color `rgb(+1, 2, 3)`, expected 3 channels from 0 to 255");
+    }
+
+    #[test]
+    fn conversions() {
+        let cases = [
+            (Color::RED, 0.0, 1.0, 0.5, 1.0, 1.0),
+            (Color::LIME, 120.0, 1.0, 0.5, 1.0, 1.0),
+            (Color::BLUE, 240.0, 1.0, 0.5, 1.0, 1.0),
+            (Color::GREEN, 120.0, 1.0, 0.251, 1.0, 0.502),
+            (Color::WHITE, 0.0, 0.0, 1.0, 0.0, 1.0),
+            (Color::GRAY, 0.0, 0.0, 0.502, 0.0, 0.502),
+            (Color::BLACK, 0.0, 0.0, 0.0, 0.0, 0.0),
+            (Color::new(128, 255, 90), 106.182, 1.0, 0.676, 0.647, 1.0),
+        ];
+        for (color, hue, s_l, lightness, s_v, value) in cases.iter() {
+            let hsl = color.to_hsl();
+            assert!(close(hsl.hue, *hue) && close(hsl.saturation, *s_l) && close(hsl.lightness, *lightness), "{} {:?}", color, hsl);
+            let hsv = color.to_hsv();
+            assert!(close(hsv.hue, *hue) && close(hsv.saturation, *s_v) && close(hsv.value, *value), "{} {:?}", color, hsv);
+        }
+
+        // every color converts back to itself
+        for hex in (0..=0xffffff).step_by(257) {
+            let color = Color::from_hex(hex);
+            assert_eq!(Color::from_hsl(color.to_hsl()), color);
+            assert_eq!(Color::from_hsv(color.to_hsv()), color);
+        }
+        // hues wrap around
+        assert_eq!(Color::from_hsl(Hsl { hue: 360.0, saturation: 1.0, lightness: 0.5 }), Color::RED);
+        assert_eq!(Color::from_hsv(Hsv { hue: -120.0, saturation: 1.0, value: 1.0 }), Color::BLUE);
+    }
+
+    #[test]
+    fn blending() {
+        assert_eq!(Color::RED.blend(Color::BLUE, 1.0), Color::RED);
+        assert_eq!(Color::RED.blend(Color::BLUE, 0.0), Color::BLUE);
+        assert_eq!(Color::WHITE.blend(Color::BLACK, 0.5), Color::new(128, 128, 128));
+        assert_eq!(Color::new(200, 100, 0).blend(Color::new(0, 100, 200), 0.25), Color::new(50, 100, 150));
+        // opacity is clamped
+        assert_eq!(Color::RED.blend(Color::BLUE, 2.0), Color::RED);
+    }
+}
@@ -0,0 +1,315 @@
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+// each valid struct type is a valid enum
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WebEvent {
+    PageLoad,
+    PageUnload,
+    KeyPress(char),
+    Paste(String),
+    Click { x: i64, y: i64 },
+}
+
+// Recorded event logs have one event per line:
+//
+//     load
+//     key x
+//     key " "
+//     paste "my text"
+//     click 20 80
+//     unload
+//
+// Words are separated by spaces. A key is a single character, quoted when it is a space, a quote
+// or a backslash; pasted text is always quoted. Quoted text may use the escapes `\"`, `\\`, `\n`
+// and `\t`. Empty lines, and lines starting with `#`, are skipped.
+
+// Where and why a log could not be parsed. Lines and columns count from 1; columns count chars.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl error::Error for ParseError {}
+
+// the events of a log, in order
+pub fn parse_log(log: &str) -> Result<Vec<WebEvent>, ParseError> {
+    let mut events = Vec::new();
+    for (number, line) in log.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let event = parse_line(line).map_err(|(column, message)| ParseError { line: number + 1, column, message })?;
+        events.push(event);
+    }
+    Ok(events)
+}
+
+// the log of `events`, which `parse_log` reads back as `events`
+pub fn format_log(events: &[WebEvent]) -> String {
+    events.iter().map(|event| format!("{}\n", event)).collect()
+}
+
+// One line of a log, as it is written by `format_log`
+impl fmt::Display for WebEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WebEvent::PageLoad => write!(f, "load"),
+            WebEvent::PageUnload => write!(f, "unload"),
+            WebEvent::KeyPress(c) if c.is_whitespace() || *c == '"' || *c == '\\' => {
+                write!(f, "key {}", quote(&c.to_string()))
+            },
+            WebEvent::KeyPress(c) => write!(f, "key {}", c),
+            WebEvent::Paste(text) => write!(f, "paste {}", quote(text)),
+            WebEvent::Click { x, y } => write!(f, "click {} {}", x, y),
+        }
+    }
+}
+
+// One line of a log; the column of an error is in the message
+impl FromStr for WebEvent {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<WebEvent, ParseError> {
+        parse_line(line).map_err(|(column, message)| ParseError { line: 1, column, message })
+    }
+}
+
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// a word or a quoted text, with the column it starts at
+struct Token {
+    column: usize,
+    text: String,
+    quoted: bool,
+}
+
+type LineError = (usize, String);
+
+fn parse_line(line: &str) -> Result<WebEvent, LineError> {
+    let tokens = tokenize(line)?;
+    // where a missing argument would be: one past the end of the line
+    let end = line.chars().count() + 1;
+    let mut tokens = tokens.into_iter();
+    let command = tokens.next().ok_or_else(|| (1, "expected an event".to_owned()))?;
+    if command.quoted {
+        return Err((command.column, "expected an event, found quoted text".to_owned()));
+    }
+
+    let mut argument = |what: &str| match tokens.next() {
+        Some(token) => Ok(token),
+        None => Err((end, format!("`{}` expects {}", command.text, what))),
+    };
+    let event = match command.text.as_str() {
+        "load" => WebEvent::PageLoad,
+        "unload" => WebEvent::PageUnload,
+        "key" => {
+            let key = argument("a key")?;
+            let mut chars = key.text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => WebEvent::KeyPress(c),
+                _ => return Err((key.column, format!("expected a single character, found `{}`", key.text))),
+            }
+        },
+        "paste" => {
+            let text = argument("quoted text")?;
+            if !text.quoted {
+                return Err((text.column, format!("expected quoted text, found `{}`", text.text)));
+            }
+            WebEvent::Paste(text.text)
+        },
+        "click" => {
+            let x = integer(argument("the x and y of the click")?)?;
+            let y = integer(argument("the y of the click")?)?;
+            WebEvent::Click { x, y }
+        },
+        other => {
+            let message = format!("unknown event `{}`, expected `load`, `unload`, `key`, `paste` or `click`", other);
+            return Err((command.column, message));
+        },
+    };
+    match tokens.next() {
+        Some(extra) => Err((extra.column, format!("unexpected `{}` after the event", extra.text))),
+        None => Ok(event),
+    }
+}
+
+fn integer(token: Token) -> Result<i64, LineError> {
+    match token.text.parse() {
+        Ok(value) if !token.quoted => Ok(value),
+        _ => Err((token.column, format!("expected an integer, found `{}`", token.text))),
+    }
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>, LineError> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().enumerate().peekable();
+    while let Some((index, c)) = chars.next() {
+        let column = index + 1;
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut text = String::new();
+        if c == '"' {
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((escape, '\\')) => match chars.next() {
+                        Some((_, '"')) => text.push('"'),
+                        Some((_, '\\')) => text.push('\\'),
+                        Some((_, 'n')) => text.push('\n'),
+                        Some((_, 't')) => text.push('\t'),
+                        _ => return Err((escape + 1, "invalid escape, expected `\\\"`, `\\\\`, `\\n` or `\\t`".to_owned())),
+                    },
+                    Some((_, c)) => text.push(c),
+                    None => return Err((column, "unterminated quoted text".to_owned())),
+                }
+            }
+            if let Some((next, c)) = chars.peek() {
+                if !c.is_whitespace() {
+                    return Err((next + 1, "expected a space after quoted text".to_owned()));
+                }
+            }
+            tokens.push(Token { column, text, quoted: true });
+        } else {
+            text.push(c);
+            while let Some((_, c)) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                text.push(*c);
+                chars.next();
+            }
+            tokens.push(Token { column, text, quoted: false });
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_log, parse_log, ParseError, WebEvent};
+
+    // every variant, with the characters that need quoting
+    fn all() -> Vec<WebEvent> {
+        vec![
+            WebEvent::PageLoad,
+            WebEvent::KeyPress('x'),
+            WebEvent::KeyPress('é'),
+            WebEvent::KeyPress(' '),
+            WebEvent::KeyPress('"'),
+            WebEvent::KeyPress('\\'),
+            WebEvent::KeyPress('\n'),
+            WebEvent::KeyPress('#'),
+            WebEvent::Paste(String::new()),
+            WebEvent::Paste("my text".to_owned()),
+            WebEvent::Paste("a \"quote\", a \\ and\ttwo\nlines".to_owned()),
+            WebEvent::Click { x: 20, y: 80 },
+            WebEvent::Click { x: i64::MIN, y: i64::MAX },
+            WebEvent::PageUnload,
+        ]
+    }
+
+    // the line and column of the error in `log`
+    fn malformed(log: &str) -> (usize, usize) {
+        let error = parse_log(log).expect_err("a malformed log was accepted");
+        (error.line, error.column)
+    }
+
+    #[test]
+    fn events_read_back_as_written() {
+        for event in all() {
+            assert_eq!(event.to_string().parse::<WebEvent>().unwrap(), event);
+        }
+        assert_eq!(parse_log(&format_log(&all())).unwrap(), all());
+        assert_eq!(format_log(&[WebEvent::Click { x: 20, y: 80 }, WebEvent::KeyPress(' ')]), "click 20 80\nkey \" \"\n");
+        assert_eq!(format_log(&[]), "");
+    }
+
+    #[test]
+    fn the_recorded_log_reads_back_as_written() {
+        let events = parse_log(include_str!("../examples/events.log")).unwrap();
+        assert!(!events.is_empty());
+        assert_eq!(parse_log(&format_log(&events)).unwrap(), events);
+    }
+
+    #[test]
+    fn spacing_and_comments() {
+        assert_eq!(parse_log("  click\t20   80  \n\n# comment\n").unwrap(), vec![WebEvent::Click { x: 20, y: 80 }]);
+        assert_eq!(parse_log("").unwrap(), vec![]);
+        assert_eq!(parse_log("   \n  # load\n").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn unknown_events() {
+        assert_eq!(malformed("load\nscroll 3"), (2, 1));
+        assert_eq!(malformed("\"load\""), (1, 1));
+        let error = "  Load".parse::<WebEvent>().unwrap_err();
+        assert_eq!(error, ParseError {
+            line: 1,
+            column: 3,
+            message: "unknown event `Load`, expected `load`, `unload`, `key`, `paste` or `click`".to_owned(),
+        });
+    }
+
+    #[test]
+    fn missing_fields() {
+        // reported one past the end of the line
+        assert_eq!(malformed("click 20"), (1, 9));
+        assert_eq!(malformed("click"), (1, 6));
+        assert_eq!(malformed("key"), (1, 4));
+        assert_eq!(malformed("paste"), (1, 6));
+        assert_eq!("click 20".parse::<WebEvent>().unwrap_err().message, "`click` expects the y of the click");
+    }
+
+    #[test]
+    fn bad_numbers() {
+        assert_eq!(malformed("click 20 eighty"), (1, 10));
+        assert_eq!(malformed("click 2.5 80"), (1, 7));
+        assert_eq!(malformed("click 20 99999999999999999999"), (1, 10));
+        // quoted numbers are text
+        assert_eq!(malformed("click \"20\" 80"), (1, 7));
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert_eq!(malformed("load\nkey xy"), (2, 5));
+        assert_eq!(malformed("paste my text"), (1, 7));
+        assert_eq!(malformed("load now"), (1, 6));
+        assert_eq!(malformed("click 1 2 3"), (1, 11));
+    }
+
+    #[test]
+    fn bad_quoting() {
+        assert_eq!(malformed("paste \"my text"), (1, 7));
+        assert_eq!(malformed("paste \"my\\q text\""), (1, 10));
+        assert_eq!(malformed("paste \"my\"text"), (1, 11));
+        // columns count characters, not bytes
+        assert_eq!(malformed("paste \"é\" é"), (1, 11));
+    }
+}
@@ -0,0 +1,383 @@
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+// the mean radius of the Earth, taken as a sphere
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+// A named place, in degrees, north and east positive: latitudes are in [-90, 90] and longitudes
+// in [-180, 180], which `new` and deserialization check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Unchecked")]
+pub struct City {
+    name: String,
+    lat: f64,
+    lon: f64,
+}
+
+// a city as it is stored, before its coordinates are checked
+#[derive(Deserialize)]
+#[serde(rename = "City")]
+struct Unchecked {
+    name: String,
+    lat: f64,
+    lon: f64,
+}
+
+impl TryFrom<Unchecked> for City {
+    type Error = GeoError;
+
+    fn try_from(city: Unchecked) -> Result<City, GeoError> {
+        City::new(city.name, city.lat, city.lon)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoError {
+    Latitude(f64),
+    Longitude(f64),
+    // text that is not a city or coordinates, and why
+    Malformed(String),
+    // a line of a CSV file, counting from 1
+    Line { line: usize, error: Box<GeoError> },
+}
+
+impl fmt::Display for GeoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GeoError::Latitude(lat) => write!(f, "latitude {} is not between -90 and 90 degrees", lat),
+            GeoError::Longitude(lon) => write!(f, "longitude {} is not between -180 and 180 degrees", lon),
+            GeoError::Malformed(message) => write!(f, "{}", message),
+            GeoError::Line { line, error } => write!(f, "line {}: {}", line, error),
+        }
+    }
+}
+
+impl error::Error for GeoError {}
+
+impl City {
+    pub fn new<S: Into<String>>(name: S, lat: f64, lon: f64) -> Result<City, GeoError> {
+        let (lat, lon) = validate(lat, lon)?;
+        Ok(City { name: name.into(), lat, lon })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn lat(&self) -> f64 {
+        self.lat
+    }
+
+    pub fn lon(&self) -> f64 {
+        self.lon
+    }
+
+    // The great-circle distance, by the haversine formula, which stays accurate for close
+    // cities; the Earth is taken as a sphere, so it can be off by up to 0.5%.
+    pub fn distance_km(&self, other: &City) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.lon - self.lon).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        // `a` can round to slightly above 1 for antipodes
+        2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
+
+    // The direction to set off in, along the great circle to `other`, in degrees clockwise from
+    // north, in [0, 360); the direction changes along the way. 0 when the cities are the same.
+    pub fn bearing(&self, other: &City) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let dlon = (other.lon - self.lon).to_radians();
+        let y = dlon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+        // rem_euclid can round -0.0 and tiny negatives up to 360
+        let bearing = y.atan2(x).to_degrees().rem_euclid(360.0);
+        if bearing >= 360.0 { 0.0 } else { bearing }
+    }
+
+    // The `k` cities of `cities` closest to this one, closest first, with their distances; this
+    // city itself is left out, if it is among them.
+    pub fn nearest<'a>(&self, cities: &'a [City], k: usize) -> Vec<(f64, &'a City)> {
+        let mut nearest = cities
+            .iter()
+            .filter(|city| *city != self)
+            .map(|city| (self.distance_km(city), city))
+            .collect::<Vec<_>>();
+        nearest.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        nearest.truncate(k);
+        nearest
+    }
+}
+
+// `Dublin: 53.348°N 6.260°W`, which `FromStr` reads back, to 3 decimals (about 100 m)
+impl fmt::Display for City {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
+        let lon_c = if self.lon >= 0.0 { 'E' } else { 'W' };
+        write!(f, "{}: {:.3}°{} {:.3}°{}", self.name, self.lat.abs(), lat_c, self.lon.abs(), lon_c)
+    }
+}
+
+// `name: coordinates`, as `parse_coordinates` reads them; the name may contain `:` itself
+impl FromStr for City {
+    type Err = GeoError;
+
+    fn from_str(text: &str) -> Result<City, GeoError> {
+        let (name, coordinates) = match text.rfind(':') {
+            Some(colon) => (text[..colon].trim(), &text[colon + 1..]),
+            None => return Err(GeoError::Malformed(format!("expected `name: coordinates`, found `{}`", text.trim()))),
+        };
+        if name.is_empty() {
+            return Err(GeoError::Malformed(format!("expected a name before `:` in `{}`", text.trim())));
+        }
+        let (lat, lon) = parse_coordinates(coordinates)?;
+        City::new(name, lat, lon)
+    }
+}
+
+// Reads `53.348°N 6.260°W` as (53.348, -6.26): the latitude, then the longitude, each a
+// non-negative number of degrees and its hemisphere (N or S, then E or W, in either case). The
+// degree signs may be left out; the ranges are checked.
+pub fn parse_coordinates(text: &str) -> Result<(f64, f64), GeoError> {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    let (lat, lon) = match words[..] {
+        [lat, lon] => (lat, lon),
+        _ => return Err(GeoError::Malformed(format!("expected a latitude and a longitude, found `{}`", text.trim()))),
+    };
+    validate(degrees(lat, 'N', 'S')?, degrees(lon, 'E', 'W')?)
+}
+
+// the coordinates, if the latitude is in [-90, 90] and the longitude in [-180, 180]
+fn validate(lat: f64, lon: f64) -> Result<(f64, f64), GeoError> {
+    // written so that NaN fails too
+    if !(-90.0..=90.0).contains(&lat) {
+        return Err(GeoError::Latitude(lat));
+    }
+    if !(-180.0..=180.0).contains(&lon) {
+        return Err(GeoError::Longitude(lon));
+    }
+    Ok((lat, lon))
+}
+
+// `53.348°N`, as degrees, negative in the `negative` hemisphere
+fn degrees(word: &str, positive: char, negative: char) -> Result<f64, GeoError> {
+    let malformed = || GeoError::Malformed(format!("expected degrees and `{}` or `{}`, found `{}`", positive, negative, word));
+    let hemisphere = word.chars().last().map(|c| c.to_ascii_uppercase());
+    let sign = match hemisphere {
+        Some(c) if c == positive => 1.0,
+        Some(c) if c == negative => -1.0,
+        _ => return Err(malformed()),
+    };
+    let number = &word[..word.len() - 1];
+    let number = number.strip_suffix('°').unwrap_or(number);
+    match number.parse::<f64>() {
+        // the hemisphere gives the sign, so `-` and `+` are rejected
+        Ok(value) if number.starts_with(|c: char| c.is_ascii_digit()) && value.is_finite() => Ok(sign * value),
+        _ => Err(malformed()),
+    }
+}
+
+// Reads cities from CSV text, one `name,lat,lon` per line with the coordinates in decimal
+// degrees, north and east positive. A first line `name,lat,lon` is a header and skipped, as are
+// empty lines. Names with commas are quoted, with `""` for a quote inside them. Errors give the
+// line they are on.
+pub fn parse_csv(csv: &str) -> Result<Vec<City>, GeoError> {
+    let mut cities = Vec::new();
+    for (number, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (number == 0 && line.eq_ignore_ascii_case("name,lat,lon")) {
+            continue;
+        }
+        let city = parse_record(line).map_err(|error| GeoError::Line { line: number + 1, error: Box::new(error) })?;
+        cities.push(city);
+    }
+    Ok(cities)
+}
+
+fn parse_record(line: &str) -> Result<City, GeoError> {
+    let fields = split_csv(line)?;
+    let (name, lat, lon) = match &fields[..] {
+        [name, lat, lon] => (name, lat, lon),
+        _ => return Err(GeoError::Malformed(format!("expected 3 fields, name, lat and lon, found {}", fields.len()))),
+    };
+    let number = |field: &str, what: &str| {
+        field.trim().parse::<f64>().map_err(|_| GeoError::Malformed(format!("expected a {}, found `{}`", what, field.trim())))
+    };
+    City::new(name.trim(), number(lat, "latitude")?, number(lon, "longitude")?)
+}
+
+fn split_csv(line: &str) -> Result<Vec<String>, GeoError> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        let mut field = String::new();
+        while chars.peek() == Some(&' ') {
+            chars.next();
+        }
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    },
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return Err(GeoError::Malformed("unterminated quoted field".to_owned())),
+                }
+            }
+            while chars.peek() == Some(&' ') {
+                chars.next();
+            }
+            if !matches!(chars.peek(), Some(',') | None) {
+                return Err(GeoError::Malformed("expected `,` after a quoted field".to_owned()));
+            }
+        } else {
+            while let Some(c) = chars.peek() {
+                if *c == ',' {
+                    break;
+                }
+                field.push(*c);
+                chars.next();
+            }
+        }
+        fields.push(field);
+        if chars.next().is_none() {
+            return Ok(fields);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_coordinates, parse_csv, City, GeoError, EARTH_RADIUS_KM};
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() <= tolerance
+    }
+
+    fn city(name: &str, lat: f64, lon: f64) -> City {
+        City::new(name, lat, lon).unwrap()
+    }
+
+    #[test]
+    fn distances() {
+        let dublin = city("Dublin", 53.347778, -6.259722);
+        let london = city("London", 51.507222, -0.1275);
+        let new_york = city("New York", 40.712778, -74.006111);
+        assert!(close(dublin.distance_km(&london), 463.0, 2.0));
+        assert!(close(london.distance_km(&new_york), 5570.0, 10.0));
+        assert_eq!(dublin.distance_km(&london), london.distance_km(&dublin));
+        assert_eq!(dublin.distance_km(&dublin), 0.0);
+
+        // a quarter and half of the equator, and the poles
+        let quarter = std::f64::consts::PI * EARTH_RADIUS_KM / 2.0;
+        assert!(close(city("A", 0.0, 0.0).distance_km(&city("B", 0.0, 90.0)), quarter, 1e-6));
+        assert!(close(city("A", 0.0, 0.0).distance_km(&city("B", 0.0, 180.0)), 2.0 * quarter, 1e-6));
+        assert!(close(city("A", 90.0, 0.0).distance_km(&city("B", -90.0, 0.0)), 2.0 * quarter, 1e-6));
+        // across the antimeridian, the short way
+        assert!(close(city("A", 0.0, 179.5).distance_km(&city("B", 0.0, -179.5)), quarter / 90.0, 1e-6));
+    }
+
+    #[test]
+    fn bearings() {
+        let origin = city("Origin", 0.0, 0.0);
+        assert!(close(origin.bearing(&city("N", 10.0, 0.0)), 0.0, 1e-9));
+        assert!(close(origin.bearing(&city("E", 0.0, 10.0)), 90.0, 1e-9));
+        assert!(close(origin.bearing(&city("S", -10.0, 0.0)), 180.0, 1e-9));
+        assert!(close(origin.bearing(&city("W", 0.0, -10.0)), 270.0, 1e-9));
+        assert_eq!(origin.bearing(&origin), 0.0);
+
+        // setting off from London to New York is north of west, as great circles bend to the pole
+        let london = city("London", 51.507222, -0.1275);
+        let new_york = city("New York", 40.712778, -74.006111);
+        assert!(close(london.bearing(&new_york), 288.3, 0.5));
+        for (from, to) in [(&london, &new_york), (&new_york, &london)] {
+            let bearing = from.bearing(to);
+            assert!((0.0..360.0).contains(&bearing));
+        }
+    }
+
+    #[test]
+    fn parsing() {
+        let dublin = city("Dublin", 53.347778, -6.259722);
+        assert_eq!(dublin.to_string(), "Dublin: 53.348°N 6.260°W");
+        assert_eq!(city("Sydney", -33.867778, 151.21).to_string(), "Sydney: 33.868°S 151.210°E");
+
+        // to the 3 decimals written
+        let parsed = dublin.to_string().parse::<City>().unwrap();
+        assert_eq!(parsed.name(), "Dublin");
+        assert!(close(parsed.lat(), dublin.lat(), 5e-4) && close(parsed.lon(), dublin.lon(), 5e-4));
+        assert_eq!(parsed.to_string().parse::<City>().unwrap(), parsed);
+
+        assert_eq!(parse_coordinates("53.348°N 6.260°W"), Ok((53.348, -6.26)));
+        assert_eq!(parse_coordinates(" 33.868s  151.21e "), Ok((-33.868, 151.21)));
+        assert_eq!(parse_coordinates("0°N 180°W"), Ok((0.0, -180.0)));
+        let city = "Washington, D.C.: 38.905°N 77.016°W".parse::<City>().unwrap();
+        assert_eq!(city.name(), "Washington, D.C.");
+        let city = "Ratio: 1:2: 1°N 2°E".parse::<City>().unwrap();
+        assert_eq!(city.name(), "Ratio: 1:2");
+
+        assert_eq!(parse_coordinates("91°N 0°E"), Err(GeoError::Latitude(91.0)));
+        assert_eq!(parse_coordinates("0°N 180.5°E"), Err(GeoError::Longitude(180.5)));
+        let errors = [
+            ("53.348°N", "expected a latitude and a longitude"),
+            ("53.348°E 6.260°W", "expected degrees and `N` or `S`, found `53.348°E`"),
+            ("53.348°N 6.260°N", "expected degrees and `E` or `W`, found `6.260°N`"),
+            ("-53.348°N 6.260°W", "found `-53.348°N`"),
+            ("53.348 6.260", "found `53.348`"),
+            ("NaN°N 0°E", "found `NaN°N`"),
+            ("°N 0°E", "found `°N`"),
+        ];
+        for (text, expected) in errors.iter() {
+            let error = parse_coordinates(text).unwrap_err().to_string();
+            assert!(error.contains(expected), "`{}` does not mention `{}`", error, expected);
+        }
+        assert!("53.348°N 6.260°W".parse::<City>().unwrap_err().to_string().contains("expected `name: coordinates`"));
+        assert!(": 53.348°N 6.260°W".parse::<City>().unwrap_err().to_string().contains("expected a name"));
+
+        assert!(matches!(City::new("Nowhere", f64::NAN, 0.0), Err(GeoError::Latitude(lat)) if lat.is_nan()));
+        assert!(matches!(City::new("Nowhere", 0.0, -180.1), Err(GeoError::Longitude(_))));
+    }
+
+    #[test]
+    fn csv() {
+        let cities = parse_csv("name,lat,lon\nDublin,53.347778,-6.259722\n\n\"Washington, D.C.\", 38.904722, -77.016389\n\"The \"\"Big\"\" Apple\",40.712778,-74.006111\n").unwrap();
+        let names = cities.iter().map(City::name).collect::<Vec<_>>();
+        assert_eq!(names, ["Dublin", "Washington, D.C.", "The \"Big\" Apple"]);
+        assert_eq!(cities[1].lat(), 38.904722);
+        assert_eq!(parse_csv("").unwrap(), []);
+
+        let errors = [
+            ("Dublin,53.347778\n", "line 1: expected 3 fields, name, lat and lon, found 2"),
+            ("name,lat,lon\nDublin,north,-6.259722\n", "line 2: expected a latitude, found `north`"),
+            ("Dublin,53.347778,-6.259722\nOslo,59.95,190\n", "line 2: longitude 190 is not between -180 and 180 degrees"),
+            ("\"Dublin,53.347778,-6.259722\n", "line 1: unterminated quoted field"),
+            ("\"Dub\"lin,53.347778,-6.259722\n", "line 1: expected `,` after a quoted field"),
+        ];
+        for (text, expected) in errors.iter() {
+            assert_eq!(parse_csv(text).unwrap_err().to_string(), *expected);
+        }
+    }
+
+    #[test]
+    fn nearest() {
+        let cities = parse_csv(include_str!("../examples/cities.csv")).unwrap();
+        let dublin = cities.iter().find(|city| city.name() == "Dublin").unwrap();
+        let nearest = dublin.nearest(&cities, 3);
+        let names = nearest.iter().map(|(_, city)| city.name()).collect::<Vec<_>>();
+        assert_eq!(names, ["Belfast", "Edinburgh", "London"]);
+        assert!(nearest.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+
+        // a place that is not in the list is measured against all of it
+        let here = City::new("Here", 53.35, -6.26).unwrap();
+        assert_eq!(here.nearest(&cities, 1)[0].1.name(), "Dublin");
+        assert_eq!(dublin.nearest(&cities, 100).len(), cities.len() - 1);
+        assert!(dublin.nearest(&cities, 0).is_empty());
+    }
+}
@@ -0,0 +1,17 @@
+// The records of the tutorial binaries (custom_types.rs and helloworld.rs) that our tools
+// exchange, with `Point` and `Rectangle` from the geometry crate, their encodings, and the
+// sessions that `WebEvent`s drive; `geo` measures between `City`s.
+pub mod codec;
+pub mod geo;
+mod color;
+mod event;
+mod person;
+mod session;
+
+pub use color::{Color, Hsl, Hsv, ParseColorError};
+pub use event::{format_log, parse_log, ParseError, WebEvent};
+pub use geo::City;
+pub use person::Person;
+pub use session::{reduce, Session, Snapshot, State};
+
+pub use geometry::{Point, Rectangle};
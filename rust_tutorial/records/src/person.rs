@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+// The person of custom_types.rs and helloworld.rs. It owns its name, so that it can be read from
+// a buffer that does not outlive it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Person {
+    pub name: String,
+    pub age: u8,
+}
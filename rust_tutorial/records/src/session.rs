@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+
+use crate::event::WebEvent;
+
+// events between two saved states of a session
+const CHECKPOINT: usize = 64;
+
+// What a sequence of events leaves a browser session in
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct State {
+    // the index of the page shown, counting loads from 0; `None` before the first load and after
+    // an unload
+    pub page: Option<usize>,
+    // pages loaded so far
+    pub loads: usize,
+    // the text typed and pasted on the page shown
+    pub text: String,
+    // the clicks on the page shown, oldest first
+    pub clicks: Vec<(i64, i64)>,
+}
+
+// The state after `event`. Loading a page starts it with no text and no clicks, and unloading it
+// leaves nothing shown; typing, pasting and clicking with nothing shown have no effect.
+pub fn reduce(mut state: State, event: &WebEvent) -> State {
+    match event {
+        WebEvent::PageLoad => {
+            state.page = Some(state.loads);
+            state.loads += 1;
+            state.text.clear();
+            state.clicks.clear();
+        },
+        WebEvent::PageUnload => {
+            state.page = None;
+            state.text.clear();
+            state.clicks.clear();
+        },
+        _ if state.page.is_none() => (),
+        WebEvent::KeyPress(c) => state.text.push(*c),
+        WebEvent::Paste(text) => state.text.push_str(text),
+        WebEvent::Click { x, y } => state.clicks.push((*x, *y)),
+    }
+    state
+}
+
+// A session is the events applied to it, in order, from a base state; its state is always the
+// result of reducing them. Undoing events forgets them and recomputes the state.
+//
+// Recomputing from the base after each undo would take as long as the session, so the session
+// also keeps the state after every `CHECKPOINT` events, and replays from the last one kept.
+#[derive(Debug, Clone)]
+pub struct Session {
+    base: State,
+    events: Vec<WebEvent>,
+    // the state after `i * CHECKPOINT` events, for each `i`
+    checkpoints: Vec<State>,
+    state: State,
+}
+
+// Everything needed to restore a session, undo included; it can be stored with `codec`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub base: State,
+    pub events: Vec<WebEvent>,
+}
+
+impl Session {
+    pub fn new() -> Session {
+        Session::starting_from(State::default())
+    }
+
+    // a session with no events, in `base`; events before it cannot be undone
+    pub fn starting_from(base: State) -> Session {
+        Session { base: base.clone(), events: Vec::new(), checkpoints: vec![base.clone()], state: base }
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn events(&self) -> &[WebEvent] {
+        &self.events
+    }
+
+    pub fn apply(&mut self, event: WebEvent) {
+        let state = std::mem::take(&mut self.state);
+        self.state = reduce(state, &event);
+        self.events.push(event);
+        if self.events.len().is_multiple_of(CHECKPOINT) {
+            self.checkpoints.push(self.state.clone());
+        }
+    }
+
+    // Forgets the last `n` events, or all of them if there are fewer, and returns them in the
+    // order they were applied, e.g. to apply them again.
+    pub fn undo(&mut self, n: usize) -> Vec<WebEvent> {
+        let len = self.events.len().saturating_sub(n);
+        let undone = self.events.split_off(len);
+        self.checkpoints.truncate(len / CHECKPOINT + 1);
+        let from = (self.checkpoints.len() - 1) * CHECKPOINT;
+        let checkpoint = self.checkpoints.last().unwrap().clone();
+        self.state = self.events[from..].iter().fold(checkpoint, reduce);
+        undone
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot { base: self.base.clone(), events: self.events.clone() }
+    }
+
+    pub fn restore(snapshot: Snapshot) -> Session {
+        let mut session = Session::starting_from(snapshot.base);
+        for event in snapshot.events {
+            session.apply(event);
+        }
+        session
+    }
+}
+
+impl Default for Session {
+    fn default() -> Session {
+        Session::new()
+    }
+}
+
+impl Extend<WebEvent> for Session {
+    fn extend<I: IntoIterator<Item = WebEvent>>(&mut self, events: I) {
+        for event in events {
+            self.apply(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reduce, Session, Snapshot, State};
+    use crate::codec::{from_binary, from_json, to_binary, to_json};
+    use crate::event::{parse_log, WebEvent};
+
+    fn click(x: i64, y: i64) -> WebEvent {
+        WebEvent::Click { x, y }
+    }
+
+    // a page being shown, with the text and clicks on it
+    fn showing(page: usize, loads: usize, text: &str, clicks: &[(i64, i64)]) -> State {
+        State { page: Some(page), loads, text: text.to_owned(), clicks: clicks.to_vec() }
+    }
+
+    #[test]
+    fn variants() {
+        let loaded = reduce(State::default(), &WebEvent::PageLoad);
+        assert_eq!(loaded, showing(0, 1, "", &[]));
+
+        let typed = reduce(loaded.clone(), &WebEvent::KeyPress('h'));
+        assert_eq!(typed, showing(0, 1, "h", &[]));
+
+        let pasted = reduce(typed, &WebEvent::Paste("ello".to_owned()));
+        assert_eq!(pasted, showing(0, 1, "hello", &[]));
+
+        let clicked = reduce(reduce(pasted, &click(20, 80)), &click(-1, 3));
+        assert_eq!(clicked, showing(0, 1, "hello", &[(20, 80), (-1, 3)]));
+
+        // a new page starts empty
+        let reloaded = reduce(clicked.clone(), &WebEvent::PageLoad);
+        assert_eq!(reloaded, showing(1, 2, "", &[]));
+
+        let unloaded = reduce(clicked, &WebEvent::PageUnload);
+        assert_eq!(unloaded, State { page: None, loads: 1, text: String::new(), clicks: Vec::new() });
+
+        // nothing shown, nothing to type into or click on
+        for event in [WebEvent::KeyPress('x'), WebEvent::Paste("x".to_owned()), click(1, 1)].iter() {
+            assert_eq!(reduce(unloaded.clone(), event), unloaded);
+            assert_eq!(reduce(State::default(), event), State::default());
+        }
+    }
+
+    #[test]
+    fn undo() {
+        let mut session = Session::new();
+        session.extend(parse_log("load\nkey a\nkey b\nclick 1 2\npaste \"cd\"").unwrap());
+        assert_eq!(session.state(), &showing(0, 1, "abcd", &[(1, 2)]));
+
+        assert_eq!(session.undo(2), vec![click(1, 2), WebEvent::Paste("cd".to_owned())]);
+        assert_eq!(session.state(), &showing(0, 1, "ab", &[]));
+        assert_eq!(session.undo(0), Vec::new());
+
+        // undone events can be applied again
+        session.extend(vec![click(1, 2), WebEvent::Paste("cd".to_owned())]);
+        assert_eq!(session.state(), &showing(0, 1, "abcd", &[(1, 2)]));
+
+        // an unload can be undone too, bringing the page back
+        session.apply(WebEvent::PageUnload);
+        session.undo(1);
+        assert_eq!(session.state(), &showing(0, 1, "abcd", &[(1, 2)]));
+
+        assert_eq!(session.undo(100).len(), 5);
+        assert_eq!(session.state(), &State::default());
+        assert!(session.events().is_empty());
+
+        // undo across checkpoints gives the state of replaying what is left from the start
+        let events = (0..1000).map(|i| match i % 7 {
+            0 => WebEvent::PageLoad,
+            3 => WebEvent::PageUnload,
+            1 | 4 => WebEvent::KeyPress((b'a' + (i % 26) as u8) as char),
+            2 => WebEvent::Paste(i.to_string()),
+            _ => click(i, -i),
+        }).collect::<Vec<_>>();
+        let mut session = Session::new();
+        session.extend(events.clone());
+        let mut len = events.len();
+        for n in [1, 63, 64, 65, 200, 3, 0, 128].iter() {
+            session.undo(*n);
+            len -= n;
+            let replayed = events[..len].iter().fold(State::default(), reduce);
+            assert_eq!(session.state(), &replayed);
+            assert_eq!(session.events(), &events[..len]);
+        }
+    }
+
+    #[test]
+    fn snapshots() {
+        let mut session = Session::new();
+        session.extend(parse_log("load\nkey x\nclick 20 80").unwrap());
+        let snapshot = session.snapshot();
+
+        session.extend(vec![WebEvent::Paste("lost".to_owned()), WebEvent::PageUnload]);
+        let mut restored = Session::restore(snapshot.clone());
+        assert_eq!(restored.state(), &showing(0, 1, "x", &[(20, 80)]));
+        // undo goes back past the snapshot
+        restored.undo(1);
+        assert_eq!(restored.state(), &showing(0, 1, "x", &[]));
+
+        // snapshots can be stored
+        let json = to_json(&snapshot);
+        assert_eq!(Session::restore(from_json(&json).unwrap()).state(), &showing(0, 1, "x", &[(20, 80)]));
+        let bytes = to_binary(&snapshot).unwrap();
+        assert_eq!(from_binary::<Snapshot>(&bytes).unwrap(), snapshot);
+
+        // a session can start from a state, without the events that led to it
+        let mut session = Session::starting_from(showing(4, 5, "typed", &[]));
+        session.apply(WebEvent::KeyPress('!'));
+        assert_eq!(session.state(), &showing(4, 5, "typed!", &[]));
+        assert_eq!(session.undo(10).len(), 1);
+        assert_eq!(session.state(), &showing(4, 5, "typed", &[]));
+    }
+}
@@ -0,0 +1,176 @@
+extern crate timely;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use timely::ExchangeData;
+use timely::dataflow::{Scope, Stream};
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::{Capture, Delay, Filter, Inspect, Map, Operator, ToStream};
+use timely::dataflow::operators::capture::Extract;
+
+use crate::examples::advanced::web_events::{hash, sample_log, WebEvent};
+use crate::examples::params::Params;
+use crate::examples::runner;
+
+pub trait Attribute<G: Scope<Timestamp=u64>, K: ExchangeData> {
+    // attributes each interaction (click, key press, paste) to the time of the most recent page
+    // load of its key; an interaction is unattributed (`None`) if the page was unloaded since, or
+    // if the load is more than `timeout` timestamps old
+    fn attribute(&self, pages: &Stream<G, (K, WebEvent)>, timeout: u64) -> Stream<G, (K, Option<u64>, WebEvent)>;
+}
+
+impl<G, K> Attribute<G, K> for Stream<G, (K, WebEvent)>
+where
+    G: Scope<Timestamp=u64>,
+    K: ExchangeData+Hash+Eq {
+
+    fn attribute(&self, pages: &Stream<G, (K, WebEvent)>, timeout: u64) -> Stream<G, (K, Option<u64>, WebEvent)> {
+        // both sides of a key must meet at the same worker
+        let interactions = Exchange::new(|x: &(K, WebEvent)| hash(&x.0));
+        let loads = Exchange::new(|x: &(K, WebEvent)| hash(&x.0));
+
+        self.binary_frontier(pages, interactions, loads, "Attribute", move |_capability, _info| {
+            // interactions by time, with the capability to send their attributions
+            let mut pending = HashMap::new();
+            // page loads and unloads by time; they produce no output, so need no capability
+            let mut changes = HashMap::new();
+            // the time of the current page load of each key
+            let mut latest = HashMap::new();
+
+            move |input1, input2, output| {
+                while let Some((time, data)) = input1.next() {
+                    pending.entry(*time.time())
+                        .or_insert_with(|| (time.retain(), Vec::new()))
+                        .1
+                        .extend(data.replace(Vec::new()));
+                }
+                while let Some((time, data)) = input2.next() {
+                    changes.entry(*time.time())
+                        .or_insert_with(Vec::new)
+                        .extend(data.replace(Vec::new()));
+                }
+
+                // a time is complete once neither input can still receive it
+                let complete = |time: &u64| {
+                    !input1.frontier().less_equal(time) && !input2.frontier().less_equal(time)
+                };
+
+                // complete times are replayed in order; at each time the page changes apply
+                // before the interactions, so a click at the time of a load belongs to it
+                let mut ready = pending.keys()
+                    .chain(changes.keys())
+                    .filter(|time| complete(time))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                ready.sort();
+                ready.dedup();
+
+                for time in ready {
+                    for (key, event) in changes.remove(&time).unwrap_or_default() {
+                        match event {
+                            WebEvent::PageLoad => { latest.insert(key, time); },
+                            WebEvent::PageUnload => { latest.remove(&key); },
+                            _ => (),
+                        }
+                    }
+                    if let Some((capability, events)) = pending.remove(&time) {
+                        let mut session = output.session(&capability);
+                        for (key, event) in events {
+                            let load = latest.get(&key).cloned().filter(|load| time <= load.saturating_add(timeout));
+                            session.give((key, load, event));
+                        }
+                    }
+                }
+
+                // loads that expired before the frontier of both inputs can not be used any more
+                latest.retain(|_key, load| !complete(&load.saturating_add(timeout)));
+            }
+        })
+    }
+}
+
+fn is_page_change(event: &WebEvent) -> bool {
+    matches!(event, WebEvent::PageLoad | WebEvent::PageUnload)
+}
+
+// the (user, page load) of interactions
+type Attributed = Vec<(String, Option<u64>)>;
+
+// the attributions of the interactions of the sample log, plus a key press after ana left
+// the page and a click on a stale page, by time
+fn sample_attributions(timeout: u64) -> Vec<(u64, Attributed)> {
+    let mut log = sample_log();
+    log.push((13, "ana".to_owned(), WebEvent::KeyPress('z')));
+    log.push((2, "cid".to_owned(), WebEvent::PageLoad));
+    log.push((9, "cid".to_owned(), WebEvent::Click { x: 0, y: 0 }));
+
+    let attributions = runner::example(move |scope| {
+        // `to_stream` sends everything at time 0, so we move each event to its own time
+        let events = log.clone()
+            .to_stream(scope)
+            .delay(|(time, _, _), _| *time)
+            .map(|(_, user, event)| (user, event));
+
+        let pages = events.filter(|(_, event)| is_page_change(event));
+        events
+            .filter(|(_, event)| !is_page_change(event))
+            .attribute(&pages, timeout)
+            .inspect_batch(|time, data| {
+                for (user, load, event) in data {
+                    match load {
+                        Some(load) => println!("{}: {} {:?} on the page loaded at {}", time, user, event, load),
+                        None => println!("{}: {} {:?} outside of any page", time, user, event),
+                    }
+                }
+            })
+            .map(|(user, load, _)| (user, load))
+            .capture()
+    });
+
+    let mut attributions = attributions.extract();
+    for (_, data) in attributions.iter_mut() {
+        data.sort();
+    }
+    attributions
+}
+
+pub fn run() {
+    let mut params = Params::new("attribution");
+    // interactions more than this many timestamps after the page load are not attributed to it
+    let timeout = params.get("timeout", 5u64);
+    params.validate();
+
+    sample_attributions(timeout);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sample_attributions;
+
+    fn some(user: &str, load: u64) -> (String, Option<u64>) {
+        (user.to_owned(), Some(load))
+    }
+
+    #[test]
+    fn interactions_go_to_the_latest_load() {
+        assert_eq!(sample_attributions(5), vec![
+            (1, vec![some("ana", 0)]),
+            (2, vec![some("ana", 0)]),
+            (3, vec![some("bob", 1)]),
+            (4, vec![some("bob", 1)]),
+            (9, vec![("cid".to_owned(), None)]),
+            (10, vec![some("ana", 9)]),
+            (11, vec![some("ana", 9)]),
+            (13, vec![("ana".to_owned(), None)]),
+        ]);
+    }
+
+    #[test]
+    fn the_largest_timeout_does_not_overflow() {
+        let attributions = sample_attributions(u64::MAX);
+        // the stale click is now attributed, the key press after the unload still is not
+        assert!(attributions.contains(&(9, vec![some("cid", 2)])));
+        assert!(attributions.contains(&(13, vec![("ana".to_owned(), None)])));
+    }
+}
@@ -2,12 +2,21 @@ extern crate timely;
 
 use timely::dataflow::operators::*;
 
+use crate::examples::params::Params;
+use crate::examples::runner;
+
 pub fn run() {
+    let mut params = Params::new("iteration");
+    let range = params.range("range", 1..5);
+    params.check(range.start > 0, "`range` must start above 0, the loops stop at 1");
+    params.validate();
+    let (start, end) = (range.start, range.end);
+
     println!("Simple loop example");
-    timely::example(|scope| {
+    runner::example(move |scope| {
         let (handle, stream) = scope.feedback(1);
 
-        (1 .. 5)
+        (start .. end)
             .to_stream(scope)
             .concat(&stream)
             .map(|x| if x % 2 == 0 {x / 2} else {3 * x + 1})
@@ -20,7 +29,7 @@ pub fn run() {
     // note we can also use scopes if necessary:
     //   see http://timelydataflow.github.io/timely-dataflow/chapter_4/chapter_4_1.html
     println!("Two loops");
-    timely::example(|scope| {
+    runner::example(move |scope| {
         let (handle0, stream0) = scope.feedback(1);
         let (handle1, stream1) = scope.feedback(1);
 
@@ -31,7 +40,7 @@ pub fn run() {
         // concatenated and no more data is passed to it; this means that only the stream0 and
         // stream1 loops will be fed when we do connect_loop
         let parts =
-            (1 .. 5)
+            (start .. end)
                 .to_stream(scope)
                 .concat(&results0)
                 .concat(&results1)
@@ -45,7 +54,7 @@ pub fn run() {
 
     // exercise
     println!("Two loops exercise");
-    timely::example(|scope| {
+    runner::example(move |scope| {
         let (handle0, stream0) = scope.feedback(1);
         let (handle1, stream1) = scope.feedback(1);
 
@@ -56,7 +65,7 @@ pub fn run() {
         // concatenated and no more data is passed to it; this means that only the stream0 and
         // stream1 loops will be fed when we do connect_loop
         let parts =
-            (1u64 .. 5)
+            (start .. end)
                 .to_stream(scope)
                 .map(|x: u64| (x, x))
                 .concat(&results0)
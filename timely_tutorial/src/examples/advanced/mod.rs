@@ -1 +1,4 @@
 pub mod iteration;
+pub mod web_events;
+pub mod sessionization;
+pub mod attribution;
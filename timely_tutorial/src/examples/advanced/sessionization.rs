@@ -0,0 +1,203 @@
+extern crate timely;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use timely::ExchangeData;
+use timely::dataflow::{InputHandle, ProbeHandle, Scope, Stream};
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::{Capture, Delay, Input, Inspect, Map, Operator, Probe, ToStream};
+use timely::dataflow::operators::capture::Extract;
+
+use crate::examples::advanced::web_events::{hash, sample_log, WebEvent};
+use crate::examples::params::Params;
+use crate::examples::runner;
+
+// A session is a maximal run of events of one key in which consecutive events are at most `gap`
+// timestamps apart.
+#[derive(Abomonation, Clone, Debug, PartialEq)]
+pub struct Session<K, E> {
+    pub key: K,
+    pub start: u64,
+    pub end: u64,
+    pub events: Vec<(u64, E)>,
+}
+
+pub trait Sessionize<G: Scope<Timestamp=u64>, K: ExchangeData, E: ExchangeData> {
+    // groups `(key, event)` records into sessions; the timestamp of a record is its event time
+    // and each session is emitted at the time of its last event, once the frontier shows that no
+    // later event can extend it
+    fn sessionize(&self, gap: u64) -> Stream<G, Session<K, E>>;
+}
+
+impl<G, K, E> Sessionize<G, K, E> for Stream<G, (K, E)>
+where
+    G: Scope<Timestamp=u64>,
+    K: ExchangeData+Hash+Eq,
+    E: ExchangeData {
+
+    fn sessionize(&self, gap: u64) -> Stream<G, Session<K, E>> {
+        // all events of a key must meet at the same worker
+        let exchange = Exchange::new(|x: &(K, E)| hash(&x.0));
+
+        self.unary_frontier(exchange, "Sessionize", move |_capability, _info| {
+            // events by time, not yet complete
+            let mut stash = HashMap::new();
+            // open sessions by key, with the capability of their last event
+            let mut open = HashMap::new();
+
+            move |input, output| {
+                while let Some((time, data)) = input.next() {
+                    stash.entry(*time.time())
+                        .or_insert_with(|| (time.retain(), Vec::new()))
+                        .1
+                        .extend(data.replace(Vec::new()));
+                }
+
+                // complete times are replayed in order, so each session sees its events sorted
+                let mut ready = stash.keys()
+                    .filter(|time| !input.frontier().less_equal(time))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                ready.sort();
+
+                for time in ready {
+                    let (capability, events) = stash.remove(&time).unwrap();
+                    for (key, event) in events {
+                        match open.remove(&key) {
+                            Some((_, mut session)) if time <= end(&session, gap) => {
+                                session.end = time;
+                                session.events.push((time, event));
+                                open.insert(key, (capability.clone(), session));
+                            },
+                            previous => {
+                                if let Some((cap, session)) = previous {
+                                    output.session(&cap).give(session);
+                                }
+                                let session = Session { key: key.clone(), start: time, end: time, events: vec![(time, event)] };
+                                open.insert(key, (capability.clone(), session));
+                            },
+                        }
+                    }
+                }
+
+                // a session is closed when the frontier passed its last event by more than `gap`;
+                // dropping its capability lets downstream operators make progress
+                open.retain(|_key, (cap, session)| {
+                    if input.frontier().less_equal(&end(session, gap)) {
+                        true
+                    } else {
+                        output.session(cap).give(session.clone());
+                        false
+                    }
+                });
+            }
+        })
+    }
+}
+
+// the last time an event could still extend the session
+fn end<K, E>(session: &Session<K, E>, gap: u64) -> u64 {
+    session.end.saturating_add(gap)
+}
+
+// (page loads, clicks, key presses or pastes) in a session
+fn summary(session: &Session<String, WebEvent>) -> (usize, usize, usize) {
+    let count = |f: fn(&WebEvent) -> bool| session.events.iter().filter(|(_, e)| f(e)).count();
+    (
+        count(|e| *e == WebEvent::PageLoad),
+        count(|e| matches!(e, WebEvent::Click { .. })),
+        count(|e| matches!(e, WebEvent::KeyPress(_) | WebEvent::Paste(_))),
+    )
+}
+
+// the (key, start, end) of sessions
+type Spans = Vec<(String, u64, u64)>;
+
+// the sessions of the sample log, by the time they come out at
+fn sample_sessions(gap: u64) -> Vec<(u64, Spans)> {
+    runner::example(move |scope| {
+        sample_log()
+            .to_stream(scope)
+            // `to_stream` sends everything at time 0, so we move each event to its own time
+            .delay(|(time, _, _), _| *time)
+            .map(|(_, user, event)| (user, event))
+            .sessionize(gap)
+            .map(|session| (session.key, session.start, session.end))
+            .capture()
+    }).extract()
+}
+
+pub fn run() {
+    let mut params = Params::new("sessionization");
+    // sessions are split by this many timestamps without events
+    let gap = params.get("gap", 3u64);
+    params.validate();
+
+    for (time, sessions) in sample_sessions(gap) {
+        for (user, start, end) in sessions {
+            println!("{}: {} active from {} to {}", time, user, start, end);
+        }
+    }
+
+    // the same events fed round by round through an input, with sessions reported as they close
+    runner::execute_from_args(std::env::args(), move |worker| {
+        let index = worker.index();
+        let mut input = InputHandle::new();
+        let mut probe = ProbeHandle::new();
+
+        worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                .sessionize(gap)
+                .inspect(move |session| {
+                    let (loads, clicks, typed) = summary(session);
+                    println!("worker {}: {} active from {} to {}: {} page loads, {} clicks, {} typed",
+                        index, session.key, session.start, session.end, loads, clicks, typed);
+                })
+                .probe_with(&mut probe);
+        });
+
+        for (time, user, event) in sample_log() {
+            input.advance_to(time);
+            if index == 0 {
+                input.send((user, event));
+            }
+            // open sessions hold capabilities for their last event, so the output can lag the
+            // input by up to `gap` timestamps; waiting for more would never finish
+            worker.step_while(|| probe.less_than(&input.time().saturating_sub(gap)));
+        }
+    }).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sample_sessions;
+
+    #[test]
+    fn sessions_come_out_at_their_last_event() {
+        assert_eq!(sample_sessions(3), vec![
+            (2, vec![("ana".to_owned(), 0, 2)]),
+            (4, vec![("bob".to_owned(), 1, 4)]),
+            (10, vec![("bob".to_owned(), 10, 10)]),
+            (12, vec![("ana".to_owned(), 9, 12)]),
+        ]);
+    }
+
+    #[test]
+    fn a_longer_gap_merges_sessions() {
+        // bob's events at 4 and 10 are now in one session, ana's at 2 and 9 still are not
+        assert_eq!(sample_sessions(6), vec![
+            (2, vec![("ana".to_owned(), 0, 2)]),
+            (10, vec![("bob".to_owned(), 1, 10)]),
+            (12, vec![("ana".to_owned(), 9, 12)]),
+        ]);
+    }
+
+    #[test]
+    fn the_largest_gap_does_not_overflow() {
+        assert_eq!(sample_sessions(u64::MAX), vec![
+            (10, vec![("bob".to_owned(), 1, 10)]),
+            (12, vec![("ana".to_owned(), 0, 12)]),
+        ]);
+    }
+}
@@ -0,0 +1,38 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+// The `WebEvent` enum from rust_tutorial/custom_types.rs; records sent between workers need to
+// be serializable, hence the `Abomonation` derive.
+#[derive(Abomonation, Clone, Debug, PartialEq)]
+pub enum WebEvent {
+    PageLoad,
+    PageUnload,
+    KeyPress(char),
+    Paste(String),
+    Click { x: i64, y: i64 },
+}
+
+// A small recorded log of (time, user, event) triples, ordered by time.
+pub fn sample_log() -> Vec<(u64, String, WebEvent)> {
+    vec![
+        (0, "ana".to_owned(), WebEvent::PageLoad),
+        (1, "ana".to_owned(), WebEvent::Click { x: 20, y: 80 }),
+        (1, "bob".to_owned(), WebEvent::PageLoad),
+        (2, "ana".to_owned(), WebEvent::KeyPress('x')),
+        (3, "bob".to_owned(), WebEvent::Paste("my text".to_owned())),
+        (4, "bob".to_owned(), WebEvent::Click { x: 5, y: 5 }),
+        (9, "ana".to_owned(), WebEvent::PageLoad),
+        (10, "ana".to_owned(), WebEvent::KeyPress('y')),
+        (10, "bob".to_owned(), WebEvent::PageUnload),
+        (11, "ana".to_owned(), WebEvent::Click { x: 1, y: 2 }),
+        (12, "ana".to_owned(), WebEvent::PageUnload),
+    ]
+}
+
+// Where to exchange the records of a key to: the same on every worker, as `DefaultHasher::new`
+// always starts from the same keys.
+pub fn hash<K: Hash>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
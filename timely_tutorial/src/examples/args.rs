@@ -0,0 +1,42 @@
+/// Returns the value of the first `key=value` argument in `args`.
+///
+/// Keys are matched whole, with or without a leading `-`, so both `-name=dataflow` and
+/// `name=dataflow` work, while `filename=x` is not a value of `name`. Note that extra arguments
+/// should not start with `-` since timely parses those as its own options.
+pub fn extract(args: &[String], key: &str) -> Option<String> {
+    args.iter()
+        .filter_map(|arg| split(arg))
+        .find(|(k, _)| *k == key)
+        .map(|(_, value)| value.to_owned())
+}
+
+/// Same as `extract`, but looks at the arguments of the current process.
+pub fn value(key: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    extract(&args, key)
+}
+
+/// The key and value of a `key=value` or `-key=value` argument. Other arguments, such as timely's
+/// own `-w2` or `--workers=2`, have none.
+pub fn split(arg: &str) -> Option<(&str, &str)> {
+    if arg.starts_with("--") {
+        return None;
+    }
+    let arg = arg.strip_prefix('-').unwrap_or(arg);
+    arg.find('=').map(|pos| (&arg[..pos], &arg[pos + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::extract;
+
+    #[test]
+    fn keys_match_whole() {
+        let args = ["filename=a", "-name=dataflow", "timeout=3", "-w2", "--out=x"].map(String::from);
+        assert_eq!(extract(&args, "name").as_deref(), Some("dataflow"));
+        assert_eq!(extract(&args, "timeout").as_deref(), Some("3"));
+        assert_eq!(extract(&args, "out"), None);
+        assert_eq!(extract(&args, "w"), None);
+        assert_eq!(extract(&["range=0..5=x".to_owned()], "range").as_deref(), Some("0..5=x"));
+    }
+}
@@ -0,0 +1,123 @@
+extern crate timely;
+
+use std::sync::mpsc::Receiver;
+
+use timely::ExchangeData;
+use timely::dataflow::{Scope, Stream};
+use timely::dataflow::operators::{Capture, Exchange, Map, ToStream};
+use timely::dataflow::operators::capture::{Event, Extract};
+
+use crate::examples::params::Params;
+use crate::examples::simulation::Simulation;
+
+// `exchange` sends each record to exactly one worker. Two other distributions come up often:
+//   - broadcasting a stream to every worker, e.g. a small lookup table that each worker joins
+//     with its part of a large stream
+//   - gathering a stream at a single worker, e.g. to print or write a final result in one place
+// Both are built from `exchange` by choosing the destinations ourselves. (Timely also has a
+// `Broadcast` trait with the same output; the two can not be imported in the same module.)
+pub trait Collectives<G: Scope, D: ExchangeData> {
+    // every worker receives every record, once from each worker that had it
+    fn broadcast(&self) -> Stream<G, D>;
+    // worker 0 receives every record; the other workers receive nothing
+    fn gather(&self) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: ExchangeData> Collectives<G, D> for Stream<G, D> {
+    fn broadcast(&self) -> Stream<G, D> {
+        let peers = self.scope().peers() as u64;
+        self.flat_map(move |x| (0..peers).map(move |worker| (worker, x.clone())))
+            .exchange(|(worker, _)| *worker)
+            .map(|(_, x)| x)
+    }
+
+    fn gather(&self) -> Stream<G, D> {
+        self.exchange(|_| 0)
+    }
+}
+
+// the records a worker received, as (sending worker, record) pairs
+type Received = Vec<(usize, u64)>;
+
+// Runs the collectives with `peers` simulated workers, each starting with records `(index, 0..3)`, and
+// returns what each worker received, sorted.
+fn received(peers: usize) -> (Vec<Received>, Vec<Received>) {
+    let mut simulation = Simulation::new(peers, 1);
+    let captures = simulation.dataflow::<u64, _, _>(|index, scope| {
+        let records = (0..3).map(move |x| (index, x)).to_stream(scope);
+        (records.broadcast().capture(), records.gather().capture())
+    });
+    simulation.run();
+
+    captures.into_iter().map(|(broadcast, gathered)| (sorted(broadcast), sorted(gathered))).unzip()
+}
+
+fn sorted(capture: Receiver<Event<u64, (usize, u64)>>) -> Received {
+    let mut data = capture.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    data.sort();
+    data
+}
+
+pub fn run() {
+    let mut params = Params::new("collectives");
+    // every worker count from 1 up to this one is run
+    let workers = params.get("workers", 4usize);
+    params.check(workers > 0, "`workers` must be positive");
+    params.validate();
+
+    for peers in 1..=workers {
+        let (broadcast, gathered) = received(peers);
+        for (index, data) in broadcast.iter().enumerate() {
+            println!("{} workers: worker {} received {} broadcast records", peers, index, data.len());
+        }
+        for (index, data) in gathered.iter().enumerate() {
+            println!("{} workers: worker {} gathered {} records", peers, index, data.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use timely::Configuration;
+    use timely::dataflow::operators::{Capture, ToStream};
+
+    use super::{sorted, Collectives, Received};
+
+    // Runs the collectives on `peers` worker threads, each starting with records `(index, 0..3)`,
+    // and returns what each worker received, sorted, with every record that was sent.
+    fn execute(peers: usize) -> (Vec<(Received, Received)>, Received) {
+        let received = timely::execute(Configuration::Process(peers), |worker| {
+            let index = worker.index();
+            let (broadcast, gathered) = worker.dataflow::<u64, _, _>(|scope| {
+                let records = (0..3).map(move |x| (index, x)).to_stream(scope);
+                (records.broadcast().capture(), records.gather().capture())
+            });
+            while worker.step() { }
+            (sorted(broadcast), sorted(gathered))
+        }).unwrap().join().into_iter().map(Result::unwrap).collect();
+        let all = (0..peers).flat_map(|index| (0..3).map(move |x| (index, x))).collect();
+        (received, all)
+    }
+
+    #[test]
+    fn broadcast_delivers_every_record_once_to_every_worker() {
+        for peers in 1..=4 {
+            let (received, all) = execute(peers);
+            assert_eq!(received.len(), peers);
+            for (broadcast, _) in received {
+                assert_eq!(broadcast, all, "{} workers", peers);
+            }
+        }
+    }
+
+    #[test]
+    fn gather_delivers_every_record_once_to_worker_0() {
+        for peers in 1..=4 {
+            let (received, all) = execute(peers);
+            for (index, (_, gathered)) in received.into_iter().enumerate() {
+                let expected = if index == 0 { all.clone() } else { Vec::new() };
+                assert_eq!(gathered, expected, "{} workers, worker {}", peers, index);
+            }
+        }
+    }
+}
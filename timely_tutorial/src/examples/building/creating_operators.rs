@@ -1,15 +1,27 @@
 extern crate timely;
 
 use std::collections::HashMap;
+use timely::Data;
+use timely::dataflow::{Scope, Stream};
 use timely::dataflow::operators::{ToStream, FrontierNotificator};
 use timely::dataflow::operators::generic::operator::Operator;
 use timely::dataflow::channels::pact::Pipeline;
 
 use timely::dataflow::operators::generic::operator::source;
 
+use crate::examples::params::Params;
+use crate::examples::runner;
+
 pub fn run() {
-    timely::example(|scope| {
-        (0u64..10)
+    let mut params = Params::new("creating_operators");
+    let range = params.range("range", 0..10);
+    let (start, end) = (range.start, range.end);
+    // the last time at which the source sends a record
+    let last = params.get("last", 21u64);
+    params.validate();
+
+    runner::example(move |scope| {
+        (start..end)
             .to_stream(scope)
             // unary operator: ready-to-assemble operator w/ 1 input and 1 output
             //   - input distribution: Pipeline - "don't move anything"
@@ -48,8 +60,8 @@ pub fn run() {
     //  - Capability<Time> is a capability that output will request before creating a session
     //  - capability argument(see above) exists so that we can construct operators with the
     //    ability to **send data before they receive** any data
-    timely::example(|scope| {
-        source(scope, "Source", |capability| {
+    runner::example(move |scope| {
+        source(scope, "Source", move |capability| {
             // capability is actually an Option<Capability<Time>>
             let mut cap = Some(capability);
 
@@ -58,7 +70,7 @@ pub fn run() {
             move |output| {
                 let mut done = false;
                 if let Some(cap) = cap.as_mut() {
-                    let time = cap.time().clone();
+                    let time = *cap.time();
 
                     // get some data and send it
                     output
@@ -68,7 +80,7 @@ pub fn run() {
                     // downgrade capability to be one step in the future; the capability downgrade
                     // will be communicated to others in the downstream
                     cap.downgrade(&(time + 1));
-                    done = time > 20;
+                    done = time >= last;
                 }
                 if done {
                     // stop transmitting
@@ -85,41 +97,47 @@ pub fn run() {
     // ==> we use Notificator - a helper that says when is safe to send data(via frontiers)
 
     // `concat` example
-    timely::example(|scope| {
-        let in1 = (0..10).to_stream(scope);
-        let in2 = (0..10).to_stream(scope);
-
-        in1.binary_frontier(&in2, Pipeline, Pipeline, "concat_buffer", |_capability, _info| {
-            let mut notificator = FrontierNotificator::new();
-
-            // HashMap<Time, Vec<Data>> used to buffer data that is not ready to send
-            let mut stash = HashMap::new();
-
-            move |input1, input2, output| {
-                // note we can't put this in a vector due to time misatch; we could call
-                // a function though
-                while let Some((time, data)) = input1.next() {
-                    stash.entry(time.time().clone())
-                        .or_insert(Vec::new())
-                        .push(data.replace(Vec::new()));
-                    notificator.notify_at(time.retain());
-                }
-                while let Some((time, data)) = input2.next() {
-                    stash.entry(time.time().clone())
-                        .or_insert(Vec::new())
-                        .push(data.replace(Vec::new()));
-                    notificator.notify_at(time.retain());
-                }
+    runner::example(move |scope| {
+        let in1 = (start..end).to_stream(scope);
+        let in2 = (start..end).to_stream(scope);
 
-                notificator.for_each(&[input1.frontier(), input2.frontier()], |time, _notificator| {
-                    let mut session = output.session(&time);
-                    if let Some(list) = stash.remove(time.time()) {
-                        for mut vector in list.into_iter() {
-                            session.give_vec(&mut vector);
-                        }
-                    }
-                });
-            }
-        });
+        concat_buffer(&in1, &in2);
     });
 }
+
+// concatenates two streams, holding back the records of each time until both inputs are done
+// with it; the records of a time are then all sent at once
+pub fn concat_buffer<G: Scope<Timestamp=u64>, D: Data>(in1: &Stream<G, D>, in2: &Stream<G, D>) -> Stream<G, D> {
+    in1.binary_frontier(in2, Pipeline, Pipeline, "concat_buffer", |_capability, _info| {
+        let mut notificator = FrontierNotificator::new();
+
+        // HashMap<Time, Vec<Data>> used to buffer data that is not ready to send
+        let mut stash = HashMap::new();
+
+        move |input1, input2, output| {
+            // note we can't put this in a vector due to time misatch; we could call
+            // a function though
+            while let Some((time, data)) = input1.next() {
+                stash.entry(*time.time())
+                    .or_insert(Vec::new())
+                    .push(data.replace(Vec::new()));
+                notificator.notify_at(time.retain());
+            }
+            while let Some((time, data)) = input2.next() {
+                stash.entry(*time.time())
+                    .or_insert(Vec::new())
+                    .push(data.replace(Vec::new()));
+                notificator.notify_at(time.retain());
+            }
+
+            notificator.for_each(&[input1.frontier(), input2.frontier()], |time, _notificator| {
+                let mut session = output.session(&time);
+                if let Some(list) = stash.remove(time.time()) {
+                    for mut vector in list.into_iter() {
+                        session.give_vec(&mut vector);
+                    }
+                }
+            });
+        }
+    })
+}
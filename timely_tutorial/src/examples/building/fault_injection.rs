@@ -0,0 +1,102 @@
+extern crate timely;
+
+use std::collections::HashMap;
+
+use timely::dataflow::{Scope, Stream};
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Delay, Operator, ToStream};
+
+use crate::examples::building::creating_operators::concat_buffer;
+use crate::examples::faults::{self, Faults, Output};
+use crate::examples::params::Params;
+
+// Counts the records of each time, but sends the count so far with every batch instead of
+// waiting for the frontier: its output depends on how the records were batched.
+fn eager_count<G: Scope<Timestamp=u64>>(stream: &Stream<G, u64>) -> Stream<G, (u64, usize)> {
+    stream.unary(Pipeline, "EagerCount", |_capability, _info| {
+        let mut counts = HashMap::new();
+        move |input, output| {
+            while let Some((time, data)) = input.next() {
+                let count = counts.entry(*time.time()).or_insert(0);
+                *count += data.len();
+                output.session(&time).give((*time.time(), *count));
+            }
+        }
+    })
+}
+
+// records spread over a few times, so that batches of different times can be reordered
+fn source<G: Scope<Timestamp=u64>>(scope: &mut G, offset: u64) -> Stream<G, u64> {
+    (0..20u64).map(move |x| x + offset).to_stream(scope).delay(|x, _| x % 4)
+}
+
+// `concat_buffer` waits for both frontiers, so the faults should not change its output
+pub fn check_concat_buffer(workers: usize, faults: Faults, seeds: u64) -> Result<Output<u64>, String> {
+    faults::check(workers, faults, seeds, |scope, injector| {
+        let in1 = injector.inject(&source(scope, 0));
+        let in2 = injector.inject(&source(scope, 100));
+        concat_buffer(&in1, &in2)
+    })
+}
+
+// splitting the batches changes the counts sent by `eager_count`
+pub fn check_eager_count(workers: usize, faults: Faults, seeds: u64) -> Result<Output<(u64, usize)>, String> {
+    faults::check(workers, faults, seeds, |scope, injector| eager_count(&injector.inject(&source(scope, 0))))
+}
+
+pub fn run() {
+    let mut params = Params::new("fault_injection");
+    let workers = params.get("workers", 2usize);
+    let seeds = params.get("seeds", 20u64);
+    params.check(workers > 0, "`workers` must be positive");
+    params.validate();
+
+    match check_concat_buffer(workers, Faults::all(), seeds) {
+        Ok(output) => println!("concat_buffer: same output for {} seeds: {:?}", seeds, output[0]),
+        Err(difference) => println!("concat_buffer changed under faults: {}", difference),
+    }
+    match check_eager_count(workers, Faults { split: true, ..Faults::default() }, seeds) {
+        Ok(_) => println!("eager_count: same output for {} seeds", seeds),
+        Err(difference) => println!("eager_count: {}", difference),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::examples::faults::Faults;
+
+    use super::{check_concat_buffer, check_eager_count};
+
+    #[test]
+    fn concat_buffer_recovers_from_every_fault() {
+        let alone = [
+            Faults { max_delay: 3, ..Faults::default() },
+            Faults { split: true, ..Faults::default() },
+            Faults { max_delay: 3, reorder: true, ..Faults::default() },
+            Faults { hold: 3, ..Faults::default() },
+            Faults::all(),
+        ];
+        // every worker sees all the records of both sources, each at its time
+        let expected = (0..4u64).map(|time| {
+            let records = (0..20u64).chain(100..120).filter(|x| x % 4 == time).collect::<Vec<_>>();
+            (time, records)
+        }).collect::<Vec<_>>();
+        for workers in 1..=3 {
+            for faults in alone.iter() {
+                let output = check_concat_buffer(workers, *faults, 10)
+                    .unwrap_or_else(|difference| panic!("{:?}: {}", faults, difference));
+                for records in output {
+                    assert_eq!(records, expected, "{:?} on {} workers", faults, workers);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn eager_count_is_caught_splitting_batches() {
+        let split = Faults { split: true, ..Faults::default() };
+        assert!(check_eager_count(2, split, 10).is_err());
+        // without faults, every run agrees with the first
+        assert!(check_eager_count(2, Faults::default(), 10).is_ok());
+    }
+}
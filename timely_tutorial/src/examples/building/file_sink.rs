@@ -0,0 +1,318 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::fs;
+use std::hash::Hash;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::rc::Rc;
+
+use timely::Data;
+use timely::communication::Allocate;
+use timely::dataflow::{InputHandle, ProbeHandle, Scope, Stream};
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Input, Inspect, Operator, Probe};
+use timely::worker::Worker;
+
+use crate::examples::params::Params;
+use crate::examples::runner;
+
+// A sink writing the records of each timestamp to their own file, so that consumers never see a
+// partial timestamp:
+//   - records are buffered per timestamp until the input frontier passes it
+//   - the file of a (worker, timestamp) pair is written to a temporary name and then renamed,
+//     which is atomic, so the file either does not exist or is complete
+//   - the timestamp is then added to the manifest of the worker, again through a rename
+//   - each file is synced before its rename, and the directory after it, so that a commit
+//     survives a power loss
+//
+// Consumers only read the files listed in the manifests. After a crash the computation is run
+// again from its start: times already in the manifest are skipped, and a file that was renamed
+// but not committed yet is simply written again with the same contents. A time that could not be
+// written is left out of the manifest, just as if the process crashed while writing it.
+pub trait FileSink<G: Scope, D: Data> {
+    // writes the records of each complete time to `dir`, failing if the manifest of the worker
+    // can not be read; the output carries each time once it was committed, so that a probe on it
+    // shows what is durable, or why it could not be committed
+    fn commit_files(&self, dir: &Path) -> io::Result<Stream<G, Result<G::Timestamp, String>>>;
+}
+
+impl<G, D> FileSink<G, D> for Stream<G, D>
+where
+    G: Scope,
+    G::Timestamp: Display+Hash+Ord,
+    D: Data+Display {
+
+    fn commit_files(&self, dir: &Path) -> io::Result<Stream<G, Result<G::Timestamp, String>>> {
+        let index = self.scope().index();
+        let dir = dir.to_path_buf();
+        fs::create_dir_all(&dir)?;
+        // the committed times, in the order they were committed, and as a set to look them up
+        let mut manifest = read_manifest(&dir, index)?;
+        let mut committed = manifest.iter().cloned().collect::<HashSet<_>>();
+
+        Ok(self.unary_frontier(Pipeline, "FileSink", move |_capability, _info| {
+            let mut stash = HashMap::new();
+
+            move |input, output| {
+                while let Some((time, data)) = input.next() {
+                    // the data of a committed time was written before the restart
+                    if committed.contains(&time.time().to_string()) {
+                        continue;
+                    }
+                    stash.entry(time.time().clone())
+                        .or_insert_with(|| (time.retain(), Vec::new()))
+                        .1
+                        .extend(data.replace(Vec::new()));
+                }
+
+                let mut ready = stash.keys()
+                    .filter(|time| !input.frontier().less_equal(time))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                // commit in time order, for the manifest to read as a log
+                ready.sort();
+                for time in ready {
+                    let (capability, records) = stash.remove(&time).unwrap();
+                    let name = time.to_string();
+                    match commit(&dir, index, &name, &records, &mut manifest) {
+                        Ok(()) => {
+                            committed.insert(name);
+                            output.session(&capability).give(Ok(time));
+                        },
+                        Err(error) => {
+                            let error = format!("cannot commit time {}: {}", name, error);
+                            output.session(&capability).give(Err(error));
+                        },
+                    }
+                }
+            }
+        }))
+    }
+}
+
+// writes the file of `time`, then adds it to the manifest; the manifest is left as it was if
+// either write fails
+fn commit<D: Display>(dir: &Path, index: usize, time: &str, records: &[D], manifest: &mut Vec<String>) -> io::Result<()> {
+    write_atomic(&file(dir, index, time), &lines(records))?;
+    manifest.push(time.to_owned());
+    let written = write_atomic(&manifest_file(dir, index), &lines(manifest));
+    if written.is_err() {
+        manifest.pop();
+    }
+    written
+}
+
+fn file(dir: &Path, index: usize, time: &str) -> PathBuf {
+    dir.join(format!("worker-{}-time-{}.txt", index, time))
+}
+
+fn manifest_file(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("manifest-{}.txt", index))
+}
+
+fn lines<T: Display>(items: &[T]) -> String {
+    items.iter().map(|item| format!("{}\n", item)).collect()
+}
+
+// writes `contents` next to `path` and renames it into place
+pub fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let temp = path.with_extension("tmp");
+    let mut file = fs::File::create(&temp)?;
+    file.write_all(contents.as_bytes())?;
+    // the contents must be on disk before the rename makes them visible
+    file.sync_all()?;
+    fs::rename(&temp, path)?;
+    // the rename itself is only durable once the directory entry is on disk
+    sync_dir(path.parent().unwrap_or_else(|| Path::new(".")))
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    fs::File::open(dir)?.sync_all()
+}
+
+// other platforms can not open a directory as a file; their renames are left to the OS
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+// the times committed by a worker; there are none before its first commit
+pub fn read_manifest(dir: &Path, index: usize) -> io::Result<Vec<String>> {
+    match fs::read_to_string(manifest_file(dir, index)) {
+        Ok(contents) => Ok(contents.lines().map(|line| line.to_owned()).collect()),
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(error),
+    }
+}
+
+// what a consumer sees: the committed records of all workers, grouped by time
+pub fn read_committed(dir: &Path, peers: usize) -> io::Result<Vec<(String, Vec<String>)>> {
+    let mut times = Vec::<(String, Vec<String>)>::new();
+    for index in 0..peers {
+        for time in read_manifest(dir, index)? {
+            let contents = fs::read_to_string(file(dir, index, &time))?;
+            let records = contents.lines().map(|line| line.to_owned());
+            match times.iter_mut().find(|(t, _)| *t == time) {
+                Some((_, all)) => all.extend(records),
+                None => times.push((time, records.collect())),
+            }
+        }
+    }
+    Ok(times)
+}
+
+pub fn run() {
+    let mut params = Params::new("file_sink");
+    let dir = PathBuf::from(params.get("dir", "sink".to_owned()));
+    let rounds = params.get("rounds", 5u64);
+    // `crash=<round>` stops the workers once that round was committed, as if the process crashed
+    let crash = params.optional::<u64>("crash");
+    params.check(crash.is_none_or(|round| round < rounds), "`crash` must be one of the rounds");
+    params.validate();
+
+    let results = runner::execute_from_args(std::env::args(), {
+        let dir = dir.clone();
+        move |worker| produce(worker, &dir, rounds, crash)
+    }).unwrap().join().into_iter().map(|result| result.unwrap()).collect::<Vec<_>>();
+
+    let errors = results.iter().filter_map(|result| result.as_ref().err()).collect::<Vec<_>>();
+    if !errors.is_empty() {
+        for error in errors {
+            eprintln!("{}", error);
+        }
+        process::exit(1);
+    }
+
+    let peers = *results[0].as_ref().unwrap();
+    match read_committed(&dir, peers) {
+        Ok(times) => {
+            for (time, records) in times {
+                println!("time {}: {:?}", time, records);
+            }
+        },
+        Err(error) => {
+            eprintln!("cannot read the committed files: {}", error);
+            process::exit(1);
+        },
+    }
+}
+
+// sends 3 records per round and worker to the sink; returns the number of workers, or why the
+// worker stopped: a failed commit, or `crash`
+fn produce<A: Allocate>(worker: &mut Worker<A>, dir: &Path, rounds: u64, crash: Option<u64>) -> Result<usize, String> {
+    let index = worker.index();
+    let mut input = InputHandle::new();
+    let mut probe = ProbeHandle::new();
+    // the first commit that failed
+    let failed = Rc::new(RefCell::new(None));
+
+    let failure = failed.clone();
+    let sink = worker.dataflow(|scope| {
+        scope.input_from(&mut input)
+            .commit_files(dir)
+            .map(|committed| {
+                committed
+                    .inspect(move |committed| match committed {
+                        Ok(time) => println!("worker {}: committed time {}", index, time),
+                        Err(error) => {
+                            failure.borrow_mut().get_or_insert_with(|| error.clone());
+                        },
+                    })
+                    .probe_with(&mut probe);
+            })
+    });
+    if let Err(error) = sink {
+        return Err(format!("worker {}: cannot open {}: {}", index, dir.display(), error));
+    }
+
+    for round in 0..rounds {
+        // the input is replayed from the start after a crash, as a log would be
+        for x in 0..3 {
+            input.send(format!("round {} worker {} record {}", round, index, x));
+        }
+        input.advance_to(round + 1);
+        worker.step_while(|| probe.less_than(input.time()));
+        let error = failed.borrow_mut().take();
+        if let Some(error) = error {
+            // unlike a crash the other workers go on; closing the input and stepping until
+            // they are done lets them finish
+            drop(input);
+            while !probe.done() {
+                worker.step();
+            }
+            return Err(format!("worker {}: {}", index, error));
+        }
+        if crash == Some(round) {
+            // returning drops the input without sending the later rounds; the workers
+            // all stop after the same round, so they shut down together
+            return Err(format!("worker {}: crashed after round {}", index, round));
+        }
+    }
+    Ok(worker.peers())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process;
+
+    use timely::Configuration;
+
+    use super::{file, produce, read_committed};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("file-sink-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn execute(workers: usize, dir: &Path, rounds: u64, crash: Option<u64>) -> Vec<Result<usize, String>> {
+        let dir = dir.to_path_buf();
+        timely::execute(Configuration::Process(workers), move |worker| produce(worker, &dir, rounds, crash))
+            .unwrap().join().into_iter().map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn a_restart_after_a_crash_commits_every_record_once() {
+        let dir = temp_dir("restart");
+        let crashed = execute(2, &dir, 4, Some(1));
+        let expected = (0..2).map(|index| Err(format!("worker {}: crashed after round 1", index)));
+        assert_eq!(crashed, expected.collect::<Vec<_>>());
+        // a crash between the rename of a file and the commit of the manifest leaves a file that
+        // is not listed, and possibly a temporary one
+        fs::write(file(&dir, 0, "2"), "partial\n").unwrap();
+        fs::write(file(&dir, 1, "2").with_extension("tmp"), "partial\n").unwrap();
+
+        assert_eq!(execute(2, &dir, 4, None), vec![Ok(2), Ok(2)]);
+        let committed = read_committed(&dir, 2).unwrap();
+        let times = committed.iter().map(|(time, _)| time.as_str()).collect::<Vec<_>>();
+        assert_eq!(times, vec!["0", "1", "2", "3"]);
+        for (round, (_, mut records)) in committed.into_iter().enumerate() {
+            records.sort();
+            let mut expected = Vec::new();
+            for worker in 0..2 {
+                for x in 0..3 {
+                    expected.push(format!("round {} worker {} record {}", round, worker, x));
+                }
+            }
+            assert_eq!(records, expected);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_directory_that_can_not_be_created_is_reported() {
+        let dir = temp_dir("blocked");
+        fs::write(&dir, "not a directory").unwrap();
+        let results = execute(1, &dir.join("sink"), 2, None);
+        let error = results[0].as_ref().unwrap_err();
+        assert!(error.starts_with("worker 0: cannot open "), "{}", error);
+        fs::remove_file(&dir).unwrap();
+    }
+}
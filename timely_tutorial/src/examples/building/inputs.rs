@@ -3,14 +3,21 @@ extern crate timely;
 use timely::dataflow::InputHandle;
 use timely::dataflow::operators::ToStream;
 
+use crate::examples::params::Params;
+use crate::examples::runner;
+
 pub fn run() {
-    timely::execute_from_args(std::env::args(), |worker| {
+    let mut params = Params::new("inputs");
+    let range = params.range("range", 0..9);
+    params.validate();
+
+    runner::execute_from_args(std::env::args(), move |worker| {
         let mut input = InputHandle::<(), String>::new();
 
         // `to_stream` - takes a scope as an argument and produces a stream in that scope.
         worker.dataflow(|scope| {
             input.to_stream(scope);
-            (0 .. 9).to_stream(scope);
+            range.clone().to_stream(scope);
         });
     }).unwrap();
 }
@@ -3,3 +3,8 @@ pub mod outputs;
 pub mod operators;
 pub mod creating_operators;
 pub mod word_count;
+pub mod rate_limited;
+pub mod file_sink;
+pub mod collectives;
+pub mod sorting;
+pub mod fault_injection;
@@ -4,14 +4,23 @@ use timely::dataflow::operators::{
     ToStream, Inspect, Map, Filter, Partition, Concatenate
 };
 
+use crate::examples::params::Params;
+use crate::examples::runner;
+
 pub fn run() {
-    timely::execute_from_args(std::env::args(), |worker| {
+    let mut params = Params::new("operators");
+    let range = params.range("range", 0..5);
+    let partitions = params.get("partitions", 3u64);
+    params.check(partitions > 0, "`partitions` must be positive");
+    params.validate();
+
+    runner::execute_from_args(std::env::args(), move |worker| {
         // - map takes owned data, so we can mutate it as we like
         // - map_in_place takes a closure which receives a mutable reference
         // - flat_map makes each element into an iterator
         // - filter receives a reference to the data
         worker.dataflow::<(),_,_>(|scope| {
-            let streams = (0 .. 5)
+            let streams = range.clone()
                 .to_stream(scope)
                 .flat_map(|x| 0 .. x)
                 .filter(|x| *x < 3)
@@ -22,7 +31,7 @@ pub fn run() {
                 .map(|x| x.to_string())
                 .map_in_place(|x| x.truncate(5))
                 .map(|x| x.parse::<u64>().unwrap())
-                .partition(3, |x| (x % 3, x));
+                .partition(partitions, move |x| (x % partitions, x));
 
             // partition only logically partitions the data
             // it does not move the data between workers
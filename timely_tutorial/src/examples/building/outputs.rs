@@ -3,18 +3,26 @@ extern crate timely;
 use timely::dataflow::operators::{ToStream, Inspect, Capture};
 use timely::dataflow::operators::capture::Extract;
 
+use crate::examples::params::Params;
+use crate::examples::runner;
+
 pub fn run() {
-    timely::execute_from_args(std::env::args(), |worker| {
+    let mut params = Params::new("outputs");
+    let inspected = params.range("inspected", 0..5);
+    let batched = params.range("batched", 0..9);
+    params.validate();
+
+    runner::execute_from_args(std::env::args(), move |worker| {
         // inspecting each worker
         worker.dataflow::<(),_,_>(|scope| {
-            (0 .. 5)
+            inspected.clone()
                 .to_stream(scope) // creates a stream in this scope
                 .inspect(|x| println!("hello: {}", x)); // and we inspect that stream
         });
 
         // inspect_batch if you want process the outputs more efficiently
         worker.dataflow::<(),_,_>(|scope| {
-            (0 .. 9)
+            batched.clone()
                 .to_stream(scope)
                 .inspect_batch(|t, xs| println!("{:?} @ {:?}", xs, t));
         });
@@ -22,7 +30,7 @@ pub fn run() {
 
     // capturing streams - the Capture trait provides a mechanism for exfiltrating a stream
     // from a dataflow, into information that can be replayed in other dataflows
-    let (data1, data2) = timely::example(|scope| {
+    let (data1, data2) = runner::example(|scope| {
         // data1 is the receive side of Rust's threadsafe channel
         //   - data1 contains events
         let data1 = (0 .. 3).to_stream(scope).capture();
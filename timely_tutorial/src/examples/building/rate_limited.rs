@@ -0,0 +1,233 @@
+extern crate timely;
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use timely::Data;
+use timely::communication::Allocate;
+use timely::dataflow::{ProbeHandle, Scope, Stream};
+use timely::dataflow::operators::{Inspect, Probe};
+use timely::dataflow::operators::generic::operator::source;
+use timely::worker::Worker;
+
+use crate::examples::params::Params;
+use crate::examples::runner;
+
+// The `source` in `creating_operators` gives a record each time it is scheduled, as fast as the
+// worker steps. A rate-limited source releases the records of an iterator at `rate` records per
+// second instead, and timestamps them with the wall-clock epoch (of length `epoch`) they were
+// released in, counted from the construction of the operator.
+//
+// Newer timely versions let an operator ask to be scheduled again after a delay, through an
+// `Activator`, and let the worker park until then. Timely 0.8 schedules every operator on each
+// step of the worker, so the source publishes the instant it wants to run next in a `Wakeup` and
+// the loop driving the worker sleeps until then, rather than spinning. Once the source is
+// exhausted the worker may still wait for the sources of other workers, whose frontiers only move
+// at epoch boundaries or when they are exhausted, so it sleeps until the next epoch boundary.
+#[derive(Clone, Default)]
+pub struct Wakeup {
+    // `None` once the source is exhausted, or before it was first scheduled
+    next: Rc<Cell<Option<Instant>>>,
+    // the start and length of the epochs, once the source was first scheduled
+    clock: Rc<Cell<Option<(Instant, Duration)>>>,
+}
+
+impl Wakeup {
+    pub fn new() -> Wakeup {
+        Default::default()
+    }
+
+    // sleeps until the source is due to release its next record, or to move to the next epoch
+    pub fn park(&self) {
+        let next = self.next.get().or_else(|| {
+            self.clock.get().map(|(start, epoch)| {
+                let current = (start.elapsed().as_nanos() / epoch.as_nanos()) as u64;
+                start + epoch_start(current + 1, epoch)
+            })
+        });
+        if let Some(next) = next {
+            let now = Instant::now();
+            if next > now {
+                thread::sleep(next - now);
+            }
+        }
+    }
+
+    fn schedule(&self, next: Option<Instant>) {
+        self.next.set(next);
+    }
+}
+
+// steps `worker` until `probe` is done, parking in between; returns the number of steps
+pub fn step_until_done<A: Allocate>(worker: &mut Worker<A>, probe: &ProbeHandle<u64>, wakeup: &Wakeup) -> usize {
+    let mut steps = 0;
+    while !probe.done() {
+        worker.step();
+        steps += 1;
+        if !probe.done() {
+            wakeup.park();
+        }
+    }
+    steps
+}
+
+pub fn rate_limited<G, I, D>(scope: &G, iter: I, rate: u64, epoch: Duration, wakeup: &Wakeup) -> Stream<G, D>
+where
+    G: Scope<Timestamp=u64>,
+    I: IntoIterator<Item=D>+'static,
+    D: Data {
+    assert!(rate > 0, "the rate must be positive");
+    assert!(epoch > Duration::from_millis(0), "the epoch must be positive");
+
+    let wakeup = wakeup.clone();
+    source(scope, "RateLimited", move |capability| {
+        let mut cap = Some(capability);
+        let mut iter = iter.into_iter().peekable();
+        let start = Instant::now();
+        wakeup.clock.set(Some((start, epoch)));
+        // number of records released so far; record `k` is due `k / rate` seconds after `start`
+        let mut released = 0u64;
+
+        move |output| {
+            let now = Instant::now();
+            let elapsed = now - start;
+            let current = (elapsed.as_nanos() / epoch.as_nanos()) as u64;
+
+            if let Some(cap) = cap.as_mut() {
+                // the epoch can only have moved forward since the last activation
+                cap.downgrade(&current);
+                let mut session = output.session(cap);
+                while iter.peek().is_some() && due(released, rate) <= elapsed {
+                    session.give(iter.next().unwrap());
+                    released += 1;
+                }
+            }
+
+            if iter.peek().is_some() {
+                // the capability must also follow the epochs in which no record is due, so that
+                // the frontier does not lag behind the clock when the rate is low
+                let next_epoch = epoch_start(current + 1, epoch);
+                wakeup.schedule(Some(start + due(released, rate).min(next_epoch)));
+            } else {
+                // exhausted: dropping the capability lets the frontier of the output become empty
+                wakeup.schedule(None);
+                cap = None;
+            }
+        }
+    })
+}
+
+// the offset from the start at which epoch `epoch` begins
+fn epoch_start(epoch: u64, length: Duration) -> Duration {
+    let nanos = u128::from(epoch) * length.as_nanos();
+    Duration::from_nanos(nanos.min(u128::from(u64::MAX)) as u64)
+}
+
+// the offset from the start at which the record with index `index` may be released
+fn due(index: u64, rate: u64) -> Duration {
+    let nanos = u128::from(index) * 1_000_000_000 / u128::from(rate);
+    Duration::from_nanos(nanos as u64)
+}
+
+pub fn run() {
+    let mut params = Params::new("rate_limited");
+    let rate = params.get("rate", 20u64);
+    let epoch = params.get("epoch", 250);
+    let count = params.get("count", 30u64);
+    params.check(rate > 0, "`rate` must be positive");
+    params.check(epoch > 0, "`epoch` must be positive");
+    params.validate();
+
+    runner::execute_from_args(std::env::args(), move |worker| {
+        let index = worker.index();
+        let peers = worker.peers() as u64;
+        let wakeup = Wakeup::new();
+        let mut probe = ProbeHandle::new();
+
+        worker.dataflow(|scope| {
+            // each worker releases its share of the records, at its share of the rate: the records
+            // of each second are dealt out as `rate` slots, and the slots to the workers in turn,
+            // so the first `rate % peers` workers release one more record per second; workers
+            // without a slot have no records
+            let share = rate / peers + u64::from((index as u64) < rate % peers);
+            let records = (0..count).filter(move |x| x % rate % peers == index as u64);
+            rate_limited(scope, records, share.max(1), Duration::from_millis(epoch), &wakeup)
+                .inspect_batch(move |epoch, data| {
+                    println!("worker {}: epoch {}: {:?}", index, epoch, data);
+                })
+                .probe_with(&mut probe);
+        });
+
+        let start = Instant::now();
+        let steps = step_until_done(worker, &probe, &wakeup);
+        println!("worker {}: done after {:?} in {} steps", index, start.elapsed(), steps);
+    }).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use timely::Configuration;
+    use timely::dataflow::ProbeHandle;
+    use timely::dataflow::operators::{Inspect, Probe};
+
+    use super::{rate_limited, step_until_done, Wakeup};
+
+    // only the first of three workers has records, `count` of them at `rate` records per second;
+    // returns the records of each epoch on the first worker, and the steps taken by each worker
+    fn release(rate: u64, epoch: Duration, count: u64) -> (Vec<(u64, Vec<u64>)>, Vec<usize>) {
+        let results = timely::execute(Configuration::Process(3), move |worker| {
+            let index = worker.index();
+            let wakeup = Wakeup::new();
+            let mut probe = ProbeHandle::new();
+            let released = Rc::new(RefCell::new(Vec::new()));
+
+            let sink = released.clone();
+            worker.dataflow(|scope| {
+                let records = if index == 0 { 0..count } else { 0..0 };
+                rate_limited(scope, records, rate, epoch, &wakeup)
+                    .inspect_batch(move |epoch, data| sink.borrow_mut().push((*epoch, data.to_vec())))
+                    .probe_with(&mut probe);
+            });
+
+            let steps = step_until_done(worker, &probe, &wakeup);
+            let released = released.replace(Vec::new());
+            (index, released, steps)
+        }).unwrap().join().into_iter().map(Result::unwrap).collect::<Vec<_>>();
+
+        let released = results.iter().find(|(index, _, _)| *index == 0).unwrap().1.clone();
+        (released, results.into_iter().map(|(_, _, steps)| steps).collect())
+    }
+
+    #[test]
+    fn records_are_released_at_the_rate() {
+        // a record every 20ms, in epochs of 50ms
+        let (released, _) = release(50, Duration::from_millis(50), 12);
+
+        let records = released.iter().flat_map(|(_, data)| data.iter().cloned()).collect::<Vec<_>>();
+        assert_eq!(records, (0..12).collect::<Vec<_>>());
+        // record `k` is not due before `k * 20ms`, i.e. before epoch `k * 20 / 50`, so up to the
+        // end of epoch `e` at most `(e + 1) * 50 / 20` records, rounded up, are released
+        let mut total = 0;
+        for (epoch, data) in released.iter() {
+            assert!(data.iter().all(|record| record * 20 / 50 <= *epoch), "{:?} released early", data);
+            total += data.len() as u64;
+            assert!(total <= ((epoch + 1) * 50).div_ceil(20), "{} records by the end of epoch {}", total, epoch);
+        }
+    }
+
+    #[test]
+    fn idle_workers_park_until_the_next_epoch() {
+        // the first worker takes about 400ms; the others wait for it, waking once per epoch rather
+        // than stepping as fast as they can
+        let (_, steps) = release(10, Duration::from_millis(50), 5);
+        for steps in steps {
+            assert!(steps < 100, "{} steps", steps);
+        }
+    }
+}
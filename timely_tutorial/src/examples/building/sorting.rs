@@ -0,0 +1,332 @@
+extern crate timely;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use timely::ExchangeData;
+use timely::dataflow::{InputHandle, Scope, Stream};
+use timely::dataflow::channels::pact::{Exchange, Pipeline};
+use timely::dataflow::operators::{Capture, Input, Operator};
+use timely::dataflow::operators::capture::Extract;
+
+use crate::examples::building::collectives::Collectives;
+use crate::examples::params::Params;
+use crate::examples::simulation::Simulation;
+
+// records each worker contributes to the sample of a timestamp
+const SAMPLES: usize = 32;
+
+// Sorting across workers, for each timestamp:
+//   - each worker sorts its records and picks `SAMPLES` evenly spaced ones; the samples are
+//     broadcast, so every worker sees the same samples
+//   - from the samples, each worker computes the same `peers - 1` splitters, which cut the key
+//     space into ranges of about the same number of records, and sends each record to the worker
+//     owning its range
+//   - each worker sorts the records of its range and emits them as one run
+// The run of worker `i` only has records smaller or equal to those of worker `i + 1`, so reading
+// the runs in worker order gives all the records of the timestamp in order.
+pub trait Sort<G: Scope, D: ExchangeData+Ord> {
+    // sorted runs, as (worker, records) pairs, one per worker and timestamp
+    fn sort_runs(&self) -> Stream<G, (usize, Vec<D>)>;
+}
+
+impl<G, D> Sort<G, D> for Stream<G, D>
+where
+    G: Scope,
+    G::Timestamp: Hash,
+    D: ExchangeData+Ord {
+
+    fn sort_runs(&self) -> Stream<G, (usize, Vec<D>)> {
+        let index = self.scope().index();
+        let peers = self.scope().peers();
+
+        let samples = self.unary_frontier(Pipeline, "Sample", |_capability, _info| {
+            let mut stash = HashMap::new();
+            move |input, output| {
+                while let Some((time, data)) = input.next() {
+                    stash.entry(time.time().clone())
+                        .or_insert_with(|| (time.retain(), Vec::new()))
+                        .1
+                        .extend(data.iter().cloned());
+                }
+                let ready = stash.keys()
+                    .filter(|time| !input.frontier().less_equal(time))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for time in ready {
+                    let (capability, mut records) = stash.remove(&time).unwrap();
+                    records.sort();
+                    let count = SAMPLES.min(records.len());
+                    let step = |j: usize| records[j * records.len() / count].clone();
+                    output.session(&capability).give_iterator((0..count).map(step));
+                }
+            }
+        }).broadcast();
+
+        self.binary_frontier(&samples, Pipeline, Pipeline, "Partition", move |_capability, _info| {
+            let mut records = HashMap::new();
+            let mut sampled = HashMap::new();
+            move |input1, input2, output| {
+                while let Some((time, data)) = input1.next() {
+                    records.entry(time.time().clone())
+                        .or_insert_with(|| (time.retain(), Vec::new()))
+                        .1
+                        .extend(data.replace(Vec::new()));
+                }
+                while let Some((time, data)) = input2.next() {
+                    sampled.entry(time.time().clone())
+                        .or_insert_with(Vec::new)
+                        .extend(data.replace(Vec::new()));
+                }
+                // the samples of a time are only complete once the second frontier passed it
+                let ready = records.keys()
+                    .filter(|time| !input1.frontier().less_equal(time) && !input2.frontier().less_equal(time))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for time in ready {
+                    let (capability, data) = records.remove(&time).unwrap();
+                    let splitters = splitters(sampled.remove(&time).unwrap_or_default(), peers);
+                    let mut session = output.session(&capability);
+                    for record in data {
+                        // the first range whose splitter is above the record
+                        let worker = splitters.iter().take_while(|splitter| **splitter <= record).count();
+                        session.give((worker as u64, record));
+                    }
+                }
+                // a time with samples but no local records has nothing to partition
+                sampled.retain(|time, _| input1.frontier().less_equal(time) || records.contains_key(time));
+            }
+        })
+        .unary_frontier(Exchange::new(|(worker, _): &(u64, D)| *worker), "SortRun", move |_capability, _info| {
+            let mut stash = HashMap::new();
+            move |input, output| {
+                while let Some((time, data)) = input.next() {
+                    stash.entry(time.time().clone())
+                        .or_insert_with(|| (time.retain(), Vec::new()))
+                        .1
+                        .extend(data.replace(Vec::new()).into_iter().map(|(_, record)| record));
+                }
+                let ready = stash.keys()
+                    .filter(|time| !input.frontier().less_equal(time))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for time in ready {
+                    let (capability, mut run) = stash.remove(&time).unwrap();
+                    run.sort();
+                    output.session(&capability).give((index, run));
+                }
+            }
+        })
+    }
+}
+
+// `peers - 1` records cutting the sorted samples into equal parts
+fn splitters<D: Ord>(mut samples: Vec<D>, peers: usize) -> Vec<D> {
+    samples.sort();
+    let count = samples.len();
+    let mut samples = samples.into_iter().enumerate();
+    (1..peers)
+        .filter_map(|i| samples.find(|(j, _)| *j == i * count / peers).map(|(_, sample)| sample))
+        .collect()
+}
+
+pub trait Histogram<G: Scope> {
+    // the number of records in each bucket `[k * width, (k + 1) * width)` of each timestamp, as
+    // (bucket start, count) pairs; empty buckets are not reported
+    fn histogram(&self, width: u64) -> Stream<G, (u64, usize)>;
+}
+
+impl<G: Scope> Histogram<G> for Stream<G, u64> where G::Timestamp: Hash {
+    fn histogram(&self, width: u64) -> Stream<G, (u64, usize)> {
+        assert!(width > 0, "buckets must not be empty");
+        // each bucket is counted by a single worker
+        let exchange = Exchange::new(move |x: &u64| *x / width);
+
+        self.unary_frontier(exchange, "Histogram", move |_capability, _info| {
+            let mut counts = HashMap::new();
+            move |input, output| {
+                while let Some((time, data)) = input.next() {
+                    let (_, buckets) = counts.entry(time.time().clone())
+                        .or_insert_with(|| (time.retain(), HashMap::new()));
+                    for x in data.iter() {
+                        *buckets.entry(x - x % width).or_insert(0) += 1;
+                    }
+                }
+                let ready = counts.keys()
+                    .filter(|time| !input.frontier().less_equal(time))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for time in ready {
+                    let (capability, buckets) = counts.remove(&time).unwrap();
+                    output.session(&capability).give_iterator(buckets.into_iter());
+                }
+            }
+        })
+    }
+}
+
+// the records given by worker `index` at `round`, from a xorshift generator
+fn records(index: usize, round: u64, count: usize, max: u64) -> Vec<u64> {
+    let mut state = (index as u64 + 1) * 7919 + round * 104_729;
+    (0..count).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % max
+    }).collect()
+}
+
+// the sorted runs of a round, in worker order, and its histogram buckets, in bucket order
+type Sorted = (Vec<(usize, Vec<u64>)>, Vec<(u64, usize)>);
+
+// the results of each round; `input(index, round)` gives the records of worker `index` at `round`
+fn sort<F>(peers: usize, rounds: u64, width: u64, input: F) -> Vec<Sorted>
+where F: Fn(usize, u64) -> Vec<u64> {
+    let mut simulation = Simulation::new(peers, 1);
+    let handles = simulation.dataflow::<u64, _, _>(|_index, scope| {
+        let mut input = InputHandle::new();
+        let stream = scope.input_from(&mut input);
+        (input, stream.sort_runs().gather().capture(), stream.histogram(width).gather().capture())
+    });
+
+    let (mut inputs, outputs): (Vec<_>, Vec<_>) = handles.into_iter()
+        .map(|(input, runs, histogram)| (input, (runs, histogram)))
+        .unzip();
+    for round in 0..rounds {
+        for (index, handle) in inputs.iter_mut().enumerate() {
+            handle.send_batch(&mut input(index, round));
+            handle.advance_to(round + 1);
+        }
+    }
+    drop(inputs);
+    simulation.run();
+
+    // everything was gathered at worker 0
+    let (runs, histogram) = outputs.into_iter().next().unwrap();
+    let runs = runs.extract();
+    let histogram = histogram.extract();
+
+    (0..rounds).map(|round| {
+        let mut sorted = runs.iter()
+            .filter(|(time, _)| *time == round)
+            .flat_map(|(_, runs)| runs.clone())
+            .collect::<Vec<_>>();
+        sorted.sort_by_key(|(worker, _)| *worker);
+        let mut buckets = histogram.iter()
+            .filter(|(time, _)| *time == round)
+            .flat_map(|(_, buckets)| buckets.clone())
+            .collect::<Vec<_>>();
+        buckets.sort();
+        (sorted, buckets)
+    }).collect()
+}
+
+pub fn run() {
+    let mut params = Params::new("sorting");
+    let workers = params.get("workers", 4usize);
+    let rounds = params.get("rounds", 3u64);
+    let count = params.get("records", 1000usize);
+    let max = params.get("max", 10_000u64);
+    let width = params.get("width", 1000u64);
+    params.check(workers > 0, "`workers` must be positive");
+    params.check(max > 0, "`max` must be positive");
+    params.check(width > 0, "`width` must be positive");
+    params.validate();
+
+    // the same records, sorted by every worker count up to `workers`
+    for peers in 1..=workers {
+        let results = sort(peers, rounds, width, |index, round| records(index, round, count, max));
+        for (round, (runs, buckets)) in results.into_iter().enumerate() {
+            // one run per worker, concatenated in worker order
+            let sizes = runs.iter().map(|(_, run)| run.len()).collect::<Vec<_>>();
+            let total = sizes.iter().sum::<usize>();
+            println!("{} workers, round {}: {} records sorted in runs of {:?}", peers, round, total, sizes);
+            if peers == workers && round == 0 {
+                for (bucket, count) in buckets {
+                    println!("  [{}, {}): {}", bucket, bucket + width, "#".repeat(count * 60 / total.max(1)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{records, sort, splitters};
+
+    // checks the runs and histogram of each round against `Vec::sort` and a plain count
+    fn check<F: Fn(usize, u64) -> Vec<u64>>(peers: usize, rounds: u64, width: u64, input: F) {
+        let results = sort(peers, rounds, width, &input);
+        assert_eq!(results.len(), rounds as usize);
+        for (round, (runs, buckets)) in results.into_iter().enumerate() {
+            let mut expected = (0..peers).flat_map(|index| input(index, round as u64)).collect::<Vec<_>>();
+            expected.sort();
+
+            // at most one run per worker, in worker order
+            let workers = runs.iter().map(|(worker, _)| *worker).collect::<Vec<_>>();
+            assert!(workers.windows(2).all(|pair| pair[0] < pair[1]), "runs of {:?}", workers);
+            assert!(workers.iter().all(|worker| *worker < peers));
+            let sorted = runs.into_iter().flat_map(|(_, run)| run).collect::<Vec<_>>();
+            assert_eq!(sorted, expected, "{} workers, round {}", peers, round);
+
+            let mut counts = HashMap::new();
+            for x in expected.iter() {
+                *counts.entry(x - x % width).or_insert(0) += 1;
+            }
+            let mut counts = counts.into_iter().collect::<Vec<_>>();
+            counts.sort();
+            assert_eq!(buckets, counts, "{} workers, round {}", peers, round);
+        }
+    }
+
+    #[test]
+    fn runs_concatenate_to_the_sorted_records() {
+        for peers in 1..=4 {
+            check(peers, 3, 1000, |index, round| records(index, round, 200, 10_000));
+        }
+    }
+
+    #[test]
+    fn duplicates_are_kept() {
+        for peers in 1..=4 {
+            check(peers, 2, 2, |index, round| records(index, round, 100, 5));
+        }
+    }
+
+    #[test]
+    fn an_empty_round_has_no_runs() {
+        let input = |index, round| if round == 1 { Vec::new() } else { records(index, round, 50, 1000) };
+        for peers in 1..=4 {
+            check(peers, 3, 100, input);
+            let results = sort(peers, 3, 100, input);
+            assert!(results[1].0.is_empty() && results[1].1.is_empty());
+        }
+    }
+
+    #[test]
+    fn a_worker_without_records() {
+        for peers in 2..=4 {
+            check(peers, 2, 100, |index, round| if index == 1 { Vec::new() } else { records(index, round, 50, 1000) });
+        }
+        // a single record, so that there are fewer samples than workers
+        for peers in 1..=4 {
+            check(peers, 1, 100, |index, _round| if index == 0 { vec![42] } else { Vec::new() });
+        }
+    }
+
+    #[test]
+    fn splitters_cut_the_samples_into_equal_parts() {
+        assert_eq!(splitters((0..8).rev().collect::<Vec<u64>>(), 4), vec![2, 4, 6]);
+        assert_eq!(splitters((0..8).collect::<Vec<u64>>(), 1), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn splitters_with_fewer_samples_than_peers() {
+        // some ranges get no splitter, so fewer than `peers - 1` are returned, still in order
+        assert_eq!(splitters(vec![5u64, 1], 4), vec![1, 5]);
+        assert_eq!(splitters(vec![7u64], 3), vec![7]);
+        assert_eq!(splitters(Vec::<u64>::new(), 3), Vec::<u64>::new());
+    }
+}
@@ -1,91 +1,572 @@
 extern crate timely;
 
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
-use timely::dataflow::{InputHandle, ProbeHandle};
-use timely::dataflow::operators::{Inspect, Probe, Map, Operator};
+use timely::communication::Allocate;
+use timely::dataflow::{InputHandle, ProbeHandle, Scope, Stream};
+use timely::dataflow::operators::{Input, Inspect, Probe, Map, Operator};
 use timely::dataflow::channels::pact::Exchange;
+use timely::worker::Worker;
 
+use crate::examples::args;
+use crate::examples::building::collectives::Collectives;
+use crate::examples::building::file_sink::write_atomic;
+use crate::examples::params::Params;
+use crate::examples::runner;
+
+// Rescaling:
+//   - `checkpoint=<dir>` stops after the last round, when that timestamp is complete, and each
+//     worker dumps the counts it owns to `<dir>/worker-<index>.txt`
+//   - `restore=<dir>` reloads the counts and continues from the checkpointed timestamp; the worker
+//     count may differ from the one that wrote the checkpoint since the state is fed back through
+//     the same `Exchange` as the words, so each count lands on the worker that now owns its key
+//
+//   cargo run -- -name=word-count checkpoint=state -w2
+//   cargo run -- -name=word-count restore=state -w3
+//
+// A missing or malformed checkpoint is reported, naming the file, before the dataflow starts; a
+// checkpoint that was interrupted has no manifest, and is reported as missing.
+//
+// Watching a directory: `watch=<dir>` counts the words of the files in `<dir>` instead, see
+// `watch` below.
 pub fn run() {
-    timely::execute_from_args(std::env::args(), |worker| {
-        let index = worker.index();
+    let mut params = Params::new("word_count");
+    let rounds = params.get("rounds", 10);
+    let checkpoint = params.optional::<String>("checkpoint");
+    let restore = params.optional::<String>("restore");
+    // the first timestamp, and the counts of each worker that wrote the checkpoint; a restored
+    // computation continues where the checkpoint stopped
+    let (start, restored) = match restore.as_ref().map(|dir| read_checkpoint(Path::new(dir))) {
+        Some(Ok(checkpoint)) => checkpoint,
+        Some(Err(error)) => {
+            params.check(false, &format!("`restore`: {}", error));
+            (0, Vec::new())
+        },
+        None => (0, Vec::new()),
+    };
+    let restored = Arc::new(restored);
+    let watched = params.optional::<String>("watch");
+    let poll = params.get("poll", 500);
+    let polls = params.get("polls", 0);
+    params.check(watched.as_ref().is_none_or(|dir| Path::new(dir).is_dir()), "`watch` must be a directory");
+    params.validate();
 
-        let mut input = InputHandle::new();
-        let mut probe = ProbeHandle::new();
+    if let Some(dir) = watched {
+        return watch(PathBuf::from(dir), Duration::from_millis(poll), polls);
+    }
 
-        // define a distribution function for strings(here by length of the text)
-        let exchange = Exchange::new(|x: &(String, i64)| (x.0).len() as u64);
-
-        worker.dataflow::<usize,_,_>(|scope| {
-            input
-                .to_stream(scope)
-                .flat_map(|(text, diff): (String, i64)| {
-                    text.split_whitespace()
-                        .map(move |word| (word.to_owned(), diff))
-                        .collect::<Vec<_>>()
-                })
-                // note that exchange does a `shuffle`
-                .unary_frontier(exchange, "WordCount", |_capability, _info| {
-                    let mut queues = HashMap::new();
-                    let mut counts = HashMap::new();
-
-                    move |input, output| {
-                        // read inputs and put them in the queue specific for `time`
-                        // we do this since **we could get data out of order**
-                        while let Some((time, data)) = input.next() {
-                            queues
-                                .entry(time.retain())
-                                .or_insert(Vec::new())
-                                .push(data.replace(Vec::new()));
-                        }
+    // a checkpoint being written has no manifest, so that a crash midway leaves nothing to restore
+    if let Some(dir) = checkpoint.as_ref() {
+        if let Err(error) = begin_checkpoint(Path::new(dir)) {
+            eprintln!("checkpoint failed: {}", error);
+            process::exit(1);
+        }
+    }
+
+    let dump = checkpoint.clone();
+    let results = runner::execute_from_args(std::env::args(), move |worker| {
+        count(worker, start, rounds, &restored, dump.as_ref().map(Path::new))
+    }).unwrap().join();
+
+    let results = results.into_iter().map(|result| result.unwrap()).collect::<Vec<_>>();
+    if let Some(dir) = checkpoint {
+        if let Err(errors) = finish_checkpoint(Path::new(&dir), &results) {
+            // without a manifest, the partial checkpoint can not be restored
+            for error in errors {
+                eprintln!("checkpoint failed: {}", error);
+            }
+            process::exit(1);
+        }
+    }
+}
 
-                        // we look at each queue; note each key is a Capability<Time>
-                        for (key, queue) in queues.iter_mut() {
-                            // input frontier says if we can expect more times or not; i.e.
-                            // if its bigger than key.time(), is clear we can process the queued
-                            // data
-                            if !input.frontier().less_equal(key.time()) {
-                                // start an output session
-                                let mut session = output.session(key);
-
-                                // each queue contains batches of data associated with that
-                                // timestamp
-                                for mut batch in queue.drain(..) {
-                                    // we go through each word
-                                    for (word, diff) in batch.drain(..) {
-                                        // update the counts
-                                        let count = counts.entry(word.clone()).or_insert(0i64);
-                                        *count += diff;
-
-                                        // give the pair to output session; that is, the next
-                                        // operator will see a stream of (word, count) pairs
-                                        session.give((word, *count));
-                                    }
+// what a worker of `count` returns: the first timestamp it did not send, the number of workers,
+// and why its counts could not be checkpointed, if they could not
+type Outcome = (usize, usize, Option<String>);
+
+// Counts the words of `rounds` rounds from `start` on, after the `restored` counts; with a
+// `checkpoint` directory, each worker dumps the counts it owns there once they are final.
+fn count<A: Allocate>(worker: &mut Worker<A>, start: usize, rounds: usize, restored: &[Counts], checkpoint: Option<&Path>) -> Outcome {
+    let index = worker.index();
+    let peers = worker.peers();
+
+    let mut input = InputHandle::new();
+    let mut probe = ProbeHandle::new();
+
+    // define a distribution function for strings(here by length of the text)
+    let exchange = Exchange::new(|x: &(String, i64)| (x.0).len() as u64);
+
+    let dump = checkpoint.map(Path::to_path_buf);
+    // why the counts of this worker could not be dumped, if they could not
+    let failed = Rc::new(RefCell::new(None));
+    let failure = failed.clone();
+    worker.dataflow::<usize,_,_>(|scope| {
+        input
+            .to_stream(scope)
+            .flat_map(|(text, diff): (String, i64)| {
+                text.split_whitespace()
+                    .map(move |word| (word.to_owned(), diff))
+                    .collect::<Vec<_>>()
+            })
+            // note that exchange does a `shuffle`
+            .unary_frontier(exchange, "WordCount", move |_capability, _info| {
+                // queued batches by time; each entry keeps the Capability<Time> we retained
+                // for sending the results of that time
+                let mut queues = HashMap::new();
+                let mut counts = HashMap::new();
+                let mut dumped = false;
+
+                move |input, output| {
+                    // read inputs and put them in the queue specific for `time`
+                    // we do this since **we could get data out of order**
+                    while let Some((time, data)) = input.next() {
+                        queues
+                            .entry(*time.time())
+                            .or_insert((time.retain(), Vec::new()))
+                            .1
+                            .push(data.replace(Vec::new()));
+                    }
+
+                    // we look at each queue
+                    for (key, queue) in queues.values_mut() {
+                        // input frontier says if we can expect more times or not; i.e.
+                        // if its bigger than key.time(), is clear we can process the queued
+                        // data
+                        if !input.frontier().less_equal(key.time()) {
+                            // start an output session
+                            let mut session = output.session(key);
+
+                            // each queue contains batches of data associated with that
+                            // timestamp
+                            for mut batch in queue.drain(..) {
+                                // we go through each word
+                                for (word, diff) in batch.drain(..) {
+                                    // update the counts
+                                    let count = counts.entry(word.clone()).or_insert(0i64);
+                                    *count += diff;
+
+                                    // give the pair to output session; that is, the next
+                                    // operator will see a stream of (word, count) pairs
+                                    session.give((word, *count));
                                 }
                             }
                         }
+                    }
+
+                    // If we don't do this we will hang in this operator; that is the session
+                    // will always get some data each time the current closure is called; this
+                    // means that the downstream will wait for us to send more messages since
+                    // we sent messages in this session
+                    queues.retain(|_key, (_, val)| !val.is_empty());
 
-                        // If we don't do this we will hang in this operator; that is the session
-                        // will always get some data each time the current closure is called; this
-                        // means that the downstream will wait for us to send more messages since
-                        // we sent messages in this session
-                        queues.retain(|_key, val| val.len() > 0)
+                    // an empty frontier means the input is closed and every timestamp was
+                    // processed, so the counts are final and can be dumped
+                    if let Some(dir) = dump.as_ref() {
+                        if !dumped && input.frontier().is_empty() {
+                            if let Err(error) = write_counts(dir, index, &counts) {
+                                *failure.borrow_mut() = Some(error);
+                            }
+                            dumped = true;
+                        }
                     }
-                })
-                .inspect(move |x| println!("worker {} sees {:?}", index, x))
+                }
+            })
+            .inspect(move |x| println!("worker {} sees {:?}", index, x))
+            .probe_with(&mut probe);
+    });
+
+    // restored counts are sent as diffs at the last checkpointed timestamp, which they
+    // summarize; the files are split between the new workers, while the exchange moves each
+    // word to its new owner
+    input.advance_to(start.saturating_sub(1));
+    for counts in restored.iter().skip(index).step_by(peers) {
+        for (word, count) in counts.iter() {
+            input.send((word.clone(), *count));
+        }
+    }
+    input.advance_to(start);
+
+    for round in start..(start + rounds) {
+        input.send(("roun one".to_owned(), 1));
+
+        // we `advance_to` to tell timely dataflow that we have ceased sending data for
+        // round and anything before it
+        input.advance_to(round + 1);
+
+        while probe.less_than(input.time()) {
+            worker.step();
+        }
+    }
+
+    // the returned time is the first timestamp that was not sent; note the input is closed
+    // when dropped, after which the workers run the dataflow to completion
+    let time = *input.time();
+    drop(input);
+    while worker.step() { }
+    let failed = failed.borrow_mut().take();
+    (time, peers, failed)
+}
+
+// Incremental word count over the files of a directory, polled every `poll` (`polls` times, or
+// forever if 0). Each new, modified or deleted file is a timestamp of its own, at which the old
+// contents of the file are retracted with a diff of -1 and the new contents added with +1, so the
+// totals always match the directory as of the last change. A poll that can not read the directory
+// is reported and changes nothing; the next poll tries again.
+//
+// Only the first worker reads the directory; the other workers close their inputs at once, so
+// the first worker alone decides which timestamps exist.
+fn watch(dir: PathBuf, poll: Duration, polls: usize) {
+    runner::execute_from_args(std::env::args(), move |worker| {
+        let index = worker.index();
+        let mut input = InputHandle::new();
+        let mut probe = ProbeHandle::new();
+        // the totals as seen by the first worker, to which all changes are gathered
+        let view = Rc::new(RefCell::new(HashMap::new()));
+
+        let seen = view.clone();
+        worker.dataflow::<u64,_,_>(|scope| {
+            watch_count(&scope.input_from(&mut input))
+                .inspect(move |total| update(&mut seen.borrow_mut(), total))
                 .probe_with(&mut probe);
         });
 
-        for round in 0..10 {
-            input.send(("roun one".to_owned(), 1));
+        if index != 0 {
+            drop(input);
+            while worker.step() { }
+            return;
+        }
 
-            // we `advance_to` to tell timely dataflow that we have ceased sending data for
-            // round and anything before it
-            input.advance_to(round + 1);
+        // the contents of each file, as of the last poll
+        let mut files = HashMap::<PathBuf, String>::new();
+        let mut round = 0;
+        while polls == 0 || round < polls {
+            let changes = match changes(&dir, &files) {
+                Ok(changes) => changes,
+                // e.g. the directory is being replaced; the next poll may read it again
+                Err(error) => {
+                    eprintln!("{}", error);
+                    Vec::new()
+                },
+            };
+            for (path, contents) in changes {
+                let time = *input.time();
+                match contents {
+                    Some(_) if files.contains_key(&path) => println!("{}: {} modified", time, path.display()),
+                    Some(_) => println!("{}: {} added", time, path.display()),
+                    None => println!("{}: {} deleted", time, path.display()),
+                }
+                if let Some(old) = files.remove(&path) {
+                    input.send((old, -1));
+                }
+                if let Some(new) = contents {
+                    input.send((new.clone(), 1));
+                    files.insert(path, new);
+                }
+                input.advance_to(time + 1);
+                worker.step_while(|| probe.less_than(input.time()));
 
-            while probe.less_than(input.time()) {
-                worker.step();
+                let mut totals = view.borrow().clone().into_iter().collect::<Vec<_>>();
+                totals.sort();
+                println!("{}: {:?}", time, totals);
             }
+            round += 1;
+            thread::sleep(poll);
         }
     }).unwrap();
 }
+
+// the files of `dir` whose contents differ from `files`, with their new contents, or `None` for
+// deleted files; files that can not be read as text are skipped
+fn changes(dir: &Path, files: &HashMap<PathBuf, String>) -> Result<Vec<(PathBuf, Option<String>)>, String> {
+    let mut current = fs::read_dir(dir)
+        .map_err(|error| format!("{}: {}", dir.display(), error))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter_map(|path| fs::read_to_string(&path).ok().map(|contents| (path, contents)))
+        .collect::<HashMap<_, _>>();
+
+    let mut changes = files.keys()
+        .filter(|path| !current.contains_key(*path))
+        .map(|path| (path.clone(), None))
+        .collect::<Vec<_>>();
+    changes.extend(current.drain().filter(|(path, contents)| files.get(path) != Some(contents)).map(|(path, contents)| (path, Some(contents))));
+    changes.sort();
+    Ok(changes)
+}
+
+// The totals of the words of the texts sent with a diff of 1 and retracted with -1, gathered at
+// the first worker: each time, the new total of each word that changed, 0 if the word is gone.
+fn watch_count<G: Scope<Timestamp=u64>>(texts: &Stream<G, (String, i64)>) -> Stream<G, (String, i64)> {
+    texts
+        .flat_map(|(text, diff): (String, i64)| {
+            text.split_whitespace()
+                .map(move |word| (word.to_owned(), diff))
+                .collect::<Vec<_>>()
+        })
+        .unary_frontier(Exchange::new(|x: &(String, i64)| (x.0).len() as u64), "WatchCount", |_capability, _info| {
+            let mut queues = HashMap::new();
+            let mut counts = HashMap::new();
+
+            move |input, output| {
+                while let Some((time, data)) = input.next() {
+                    queues
+                        .entry(*time.time())
+                        .or_insert((time.retain(), Vec::new()))
+                        .1
+                        .extend(data.replace(Vec::new()));
+                }
+
+                // unlike `run`, only the total of each changed word is sent, once per
+                // time; a total of 0 means the word is gone
+                let mut ready = queues.keys()
+                    .filter(|time| !input.frontier().less_equal(time))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                ready.sort();
+                for time in ready {
+                    let (capability, diffs) = queues.remove(&time).unwrap();
+                    let mut changed = Vec::new();
+                    for (word, diff) in diffs {
+                        *counts.entry(word.clone()).or_insert(0i64) += diff;
+                        changed.push(word);
+                    }
+                    changed.sort();
+                    changed.dedup();
+
+                    let mut session = output.session(&capability);
+                    for word in changed {
+                        let count = counts[&word];
+                        if count == 0 {
+                            counts.remove(&word);
+                        }
+                        session.give((word, count));
+                    }
+                }
+            }
+        })
+        .gather()
+}
+
+// applies a total sent by `watch_count` to the totals seen so far
+fn update(totals: &mut HashMap<String, i64>, (word, count): &(String, i64)) {
+    if *count == 0 {
+        totals.remove(word);
+    } else {
+        totals.insert(word.clone(), *count);
+    }
+}
+
+// The manifest records the timestamp at which the checkpointed computation stopped and how many
+// workers wrote their counts. It is removed before the counts are written and written last, each
+// file replaced whole by a rename, so a checkpoint with a manifest is complete.
+fn begin_checkpoint(dir: &Path) -> Result<(), String> {
+    let path = dir.join("manifest");
+    match fs::remove_file(&path) {
+        Err(ref error) if error.kind() != io::ErrorKind::NotFound => Err(format!("{}: {}", path.display(), error)),
+        _ => fs::create_dir_all(dir).map_err(|error| format!("{}: {}", dir.display(), error)),
+    }
+}
+
+// writes the manifest once every worker dumped its counts, or returns why they could not
+fn finish_checkpoint(dir: &Path, results: &[Outcome]) -> Result<(), Vec<String>> {
+    let errors = results.iter().filter_map(|(_, _, error)| error.clone()).collect::<Vec<_>>();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    // all workers stop at the same time, so any of them can describe the checkpoint
+    match results.first() {
+        Some((time, peers, _)) => write_manifest(dir, *time, *peers).map_err(|error| vec![error]),
+        None => Ok(()),
+    }
+}
+
+fn write_manifest(dir: &Path, time: usize, workers: usize) -> Result<(), String> {
+    let path = dir.join("manifest");
+    write_atomic(&path, &format!("time={}\nworkers={}\n", time, workers))
+        .map_err(|error| format!("{}: {}", path.display(), error))
+}
+
+fn read_manifest(dir: &Path) -> Result<(usize, usize), String> {
+    let path = dir.join("manifest");
+    let text = fs::read_to_string(&path)
+        .map_err(|error| format!("{}: {}, expected a directory written by `checkpoint`", path.display(), error))?;
+    let lines = text.lines().map(|line| line.to_owned()).collect::<Vec<_>>();
+    let field = |key| args::extract(&lines, key)
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("{}: malformed manifest, expected `{}=<n>`", path.display(), key));
+    Ok((field("time")?, field("workers")?))
+}
+
+// the counts of one worker
+type Counts = Vec<(String, i64)>;
+
+// the timestamp the checkpoint in `dir` stopped at, and the counts each worker wrote
+fn read_checkpoint(dir: &Path) -> Result<(usize, Vec<Counts>), String> {
+    let (time, workers) = read_manifest(dir)?;
+    let counts = (0..workers).map(|index| read_counts(dir, index)).collect::<Result<_, _>>()?;
+    Ok((time, counts))
+}
+
+fn write_counts(dir: &Path, index: usize, counts: &HashMap<String, i64>) -> Result<(), String> {
+    let path = dir.join(format!("worker-{}.txt", index));
+    let contents = counts.iter().map(|(word, count)| format!("{}\t{}\n", word, count)).collect::<String>();
+    write_atomic(&path, &contents).map_err(|error| format!("{}: {}", path.display(), error))
+}
+
+fn read_counts(dir: &Path, index: usize) -> Result<Counts, String> {
+    let path = dir.join(format!("worker-{}.txt", index));
+    let error = |line: usize, message: String| format!("{}:{}: {}", path.display(), line, message);
+    let file = File::open(&path).map_err(|error| format!("{}: {}", path.display(), error))?;
+    let mut counts = Vec::new();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| error(number + 1, e.to_string()))?;
+        let mut parts = line.split('\t');
+        let word = parts.next().unwrap().to_owned();
+        let count = parts.next()
+            .filter(|_| parts.next().is_none())
+            .and_then(|count| count.parse().ok())
+            .ok_or_else(|| error(number + 1, format!("malformed count `{}`, expected `<word>\\t<count>`", line)))?;
+        counts.push((word, count));
+    }
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process;
+    use std::rc::Rc;
+
+    use timely::Configuration;
+    use timely::dataflow::{InputHandle, ProbeHandle};
+    use timely::dataflow::operators::{Input, Inspect, Probe};
+
+    use super::{begin_checkpoint, count, finish_checkpoint, read_checkpoint, update, watch_count, Counts};
+
+    // an empty directory of its own for each test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("word-count-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    // Runs `count` on `peers` workers as `run` does, checkpointing into `dir`, and returns the
+    // checkpoint read back: the time it stopped at, and the total of each word over all workers.
+    fn checkpoint(peers: usize, start: usize, rounds: usize, restored: Vec<Counts>, dir: &Path) -> (usize, HashMap<String, i64>) {
+        begin_checkpoint(dir).unwrap();
+        let path = dir.to_path_buf();
+        let results = timely::execute(Configuration::Process(peers), move |worker| {
+            count(worker, start, rounds, &restored, Some(&path))
+        }).unwrap().join().into_iter().map(Result::unwrap).collect::<Vec<_>>();
+        finish_checkpoint(dir, &results).unwrap();
+
+        let (time, counts) = read_checkpoint(dir).unwrap();
+        assert_eq!(counts.len(), peers);
+        let mut totals = HashMap::new();
+        for (word, count) in counts.into_iter().flatten() {
+            // each word is owned by a single worker
+            assert!(totals.insert(word, count).is_none());
+        }
+        (time, totals)
+    }
+
+    #[test]
+    fn a_checkpoint_restores_on_a_different_number_of_workers() {
+        let (first, second) = (temp_dir("first"), temp_dir("second"));
+        // every worker sends `roun one` once per round
+        let expected = |count: i64| vec![("roun".to_owned(), count), ("one".to_owned(), count)].into_iter().collect();
+        let (time, totals) = checkpoint(2, 0, 3, Vec::new(), &first);
+        assert_eq!(time, 3);
+        assert_eq!(totals, expected(2 * 3));
+
+        for peers in [1, 3] {
+            let (_, restored) = read_checkpoint(&first).unwrap();
+            let (time, totals) = checkpoint(peers, 3, 2, restored, &second);
+            assert_eq!(time, 5, "{} workers", peers);
+            assert_eq!(totals, expected(2 * 3 + peers as i64 * 2), "{} workers", peers);
+        }
+        fs::remove_dir_all(&first).unwrap();
+        fs::remove_dir_all(&second).unwrap();
+    }
+
+    #[test]
+    fn an_unfinished_checkpoint_can_not_be_restored() {
+        let dir = temp_dir("unfinished");
+        checkpoint(2, 0, 1, Vec::new(), &dir);
+        assert!(read_checkpoint(&dir).is_ok());
+        // e.g. a crash before the new manifest is written
+        begin_checkpoint(&dir).unwrap();
+        let error = read_checkpoint(&dir).unwrap_err();
+        assert!(error.contains("expected a directory written by `checkpoint`"), "{}", error);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn totals<'a, I: Iterator<Item=&'a String>>(texts: I) -> HashMap<String, i64> {
+        let mut totals = HashMap::new();
+        for text in texts {
+            for word in text.split_whitespace() {
+                *totals.entry(word.to_owned()).or_insert(0) += 1;
+            }
+        }
+        totals
+    }
+
+    // Files added, modified and deleted one at a time, as `watch` sees them: after each change,
+    // the totals gathered at the first worker must be those of the files as they are now.
+    #[test]
+    fn totals_follow_the_files() {
+        let changes = vec![
+            ("a", Some("the cat sat")),
+            ("b", Some("the dog")),
+            ("a", Some("the cat sat on the mat")),
+            ("b", None),
+            ("c", Some("")),
+            ("a", None),
+        ];
+        for peers in 1..=3 {
+            let changes = changes.clone();
+            timely::execute(Configuration::Process(peers), move |worker| {
+                let mut input = InputHandle::new();
+                let mut probe = ProbeHandle::new();
+                let view = Rc::new(RefCell::new(HashMap::new()));
+                let seen = view.clone();
+                worker.dataflow::<u64,_,_>(|scope| {
+                    watch_count(&scope.input_from(&mut input))
+                        .inspect(move |total| update(&mut seen.borrow_mut(), total))
+                        .probe_with(&mut probe);
+                });
+                if worker.index() != 0 {
+                    drop(input);
+                    while worker.step() { }
+                    return;
+                }
+
+                let mut files = HashMap::new();
+                for (path, contents) in changes.iter() {
+                    if let Some(old) = files.remove(path) {
+                        input.send((old, -1));
+                    }
+                    if let Some(new) = contents {
+                        input.send((new.to_string(), 1));
+                        files.insert(*path, new.to_string());
+                    }
+                    input.advance_to(*input.time() + 1);
+                    worker.step_while(|| probe.less_than(input.time()));
+                    assert_eq!(*view.borrow(), totals(files.values()), "{} workers", peers);
+                }
+            }).unwrap();
+        }
+    }
+}
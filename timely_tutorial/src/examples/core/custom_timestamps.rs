@@ -0,0 +1,329 @@
+extern crate timely;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use timely::order::{PartialOrder, TotalOrder};
+use timely::progress::timestamp::{PathSummary, Refines, Timestamp};
+use timely::dataflow::{InputHandle, ProbeHandle, Scope, Stream};
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Capture, Exchange, FrontierNotificator, Input, Operator, Probe};
+use timely::dataflow::operators::capture::Extract;
+
+use crate::examples::params::Params;
+use crate::examples::simulation::Simulation;
+
+// Timestamps do not have to be integers: any type implementing `Timestamp` works, that is a
+// partially ordered type with a `Summary` describing how timestamps advance along a path (only
+// loops advance timestamps, via `feedback`). Top-level dataflows also need `Refines<()>`.
+
+// Join and meet, as used by differential dataflow; for totally ordered timestamps they are the
+// maximum and the minimum.
+pub trait Lattice: PartialOrder {
+    // the least timestamp greater or equal to both
+    fn join(&self, other: &Self) -> Self;
+    // the greatest timestamp less or equal to both
+    fn meet(&self, other: &Self) -> Self;
+}
+
+// Wall-clock time, in milliseconds since the Unix epoch; the summary is a delay in milliseconds.
+#[derive(Abomonation, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Millis(pub u64);
+
+impl Millis {
+    pub fn now() -> Millis {
+        let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        Millis(elapsed.as_secs() * 1000 + u64::from(elapsed.subsec_millis()))
+    }
+
+    // the start of the `length` milliseconds window containing this time; panics if `length` is 0,
+    // as there are no empty windows
+    pub fn window(self, length: u64) -> Millis {
+        assert!(length > 0, "a window must last at least 1ms");
+        Millis(self.0 - self.0 % length)
+    }
+}
+
+impl fmt::Display for Millis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}ms", self.0)
+    }
+}
+
+impl PartialOrder for Millis {
+    fn less_equal(&self, other: &Self) -> bool { self.0 <= other.0 }
+}
+
+impl TotalOrder for Millis { }
+
+impl Lattice for Millis {
+    fn join(&self, other: &Self) -> Self { *self.max(other) }
+    fn meet(&self, other: &Self) -> Self { *self.min(other) }
+}
+
+impl Timestamp for Millis { type Summary = Millis; }
+
+impl PathSummary<Millis> for Millis {
+    fn results_in(&self, src: &Millis) -> Option<Millis> { self.0.checked_add(src.0).map(Millis) }
+    fn followed_by(&self, other: &Millis) -> Option<Millis> { self.0.checked_add(other.0).map(Millis) }
+}
+
+impl Refines<()> for Millis {
+    fn to_inner(_: ()) -> Millis { Default::default() }
+    fn to_outer(self) { }
+    fn summarize(_: Millis) { }
+}
+
+// An epoch with a sequence number inside of it, ordered lexicographically: all times of epoch 1
+// come after all times of epoch 0, whatever their sequence numbers.
+#[derive(Abomonation, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EpochSeq {
+    pub epoch: u64,
+    pub seq: u64,
+}
+
+impl EpochSeq {
+    pub fn new(epoch: u64, seq: u64) -> EpochSeq { EpochSeq { epoch, seq } }
+}
+
+impl fmt::Display for EpochSeq {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.epoch, self.seq)
+    }
+}
+
+impl PartialOrder for EpochSeq {
+    // the derived `Ord` compares the fields in order, i.e. lexicographically
+    fn less_equal(&self, other: &Self) -> bool { self <= other }
+}
+
+impl TotalOrder for EpochSeq { }
+
+impl Lattice for EpochSeq {
+    fn join(&self, other: &Self) -> Self { *self.max(other) }
+    fn meet(&self, other: &Self) -> Self { *self.min(other) }
+}
+
+impl Timestamp for EpochSeq { type Summary = EpochSeqSummary; }
+
+impl Refines<()> for EpochSeq {
+    fn to_inner(_: ()) -> EpochSeq { Default::default() }
+    fn to_outer(self) { }
+    fn summarize(_: EpochSeqSummary) { }
+}
+
+// Advances by `epochs` epochs and then by `seqs` sequence numbers; moving to a later epoch
+// restarts the sequence, so the sequence number of the source does not matter then.
+#[derive(Abomonation, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EpochSeqSummary {
+    pub epochs: u64,
+    pub seqs: u64,
+}
+
+impl PartialOrder for EpochSeqSummary {
+    // lexicographic as well: a summary that advances more epochs always gives a later time
+    fn less_equal(&self, other: &Self) -> bool { self <= other }
+}
+
+impl PathSummary<EpochSeq> for EpochSeqSummary {
+    fn results_in(&self, src: &EpochSeq) -> Option<EpochSeq> {
+        if self.epochs > 0 {
+            src.epoch.checked_add(self.epochs).map(|epoch| EpochSeq::new(epoch, self.seqs))
+        } else {
+            src.seq.checked_add(self.seqs).map(|seq| EpochSeq::new(src.epoch, seq))
+        }
+    }
+
+    fn followed_by(&self, other: &Self) -> Option<Self> {
+        if other.epochs > 0 {
+            self.epochs.checked_add(other.epochs).map(|epochs| EpochSeqSummary { epochs, seqs: other.seqs })
+        } else {
+            self.seqs.checked_add(other.seqs).map(|seqs| EpochSeqSummary { epochs: self.epochs, seqs })
+        }
+    }
+}
+
+// Counts the records of each timestamp once it is complete, whatever the timestamp type is; the
+// records are first exchanged, so the counts of a time are split between the workers.
+fn count_per_time<G: Scope>(stream: &Stream<G, u64>) -> Stream<G, (G::Timestamp, usize)>
+where G::Timestamp: Hash {
+    stream
+        .exchange(|x| *x)
+        .unary_frontier(Pipeline, "CountPerTime", |_capability, _info| {
+            let mut notificator = FrontierNotificator::new();
+            let mut counts = HashMap::new();
+
+            move |input, output| {
+                while let Some((time, data)) = input.next() {
+                    *counts.entry(time.time().clone()).or_insert(0) += data.len();
+                    notificator.notify_at(time.retain());
+                }
+                notificator.for_each(&[input.frontier()], |time, _notificator| {
+                    if let Some(count) = counts.remove(time.time()) {
+                        output.session(&time).give((time.time().clone(), count));
+                    }
+                });
+            }
+        })
+}
+
+// Feeds `rounds` to two simulated workers, one batch of records per time, and returns the total
+// count seen for each time, ordered by time.
+fn simulate<T>(rounds: Vec<(T, Vec<u64>)>) -> Vec<(T, usize)>
+where T: Timestamp+Refines<()>+TotalOrder+Copy+fmt::Display {
+    let mut simulation = Simulation::new(2, 1);
+
+    let mut handles = simulation.dataflow(|_index, scope| {
+        let mut input = InputHandle::new();
+        let mut probe = ProbeHandle::new();
+        let counts = count_per_time(&scope.input_from(&mut input))
+            .probe_with(&mut probe)
+            .capture();
+        (input, probe, counts)
+    });
+
+    for (time, records) in rounds {
+        for (input, _, _) in handles.iter_mut() {
+            input.advance_to(time);
+        }
+        handles[0].0.send_batch(&mut records.clone());
+        println!("sent {} records at {}", records.len(), time);
+    }
+
+    let (inputs, outputs): (Vec<_>, Vec<_>) = handles.into_iter()
+        .map(|(input, probe, capture)| (input, (probe, capture)))
+        .unzip();
+
+    // closing the inputs completes all times; the probes then see an empty frontier
+    drop(inputs);
+    simulation.run();
+    assert!(outputs.iter().all(|(probe, _)| probe.done()));
+
+    let mut totals = Vec::<(T, usize)>::new();
+    for (_, capture) in outputs {
+        for (time, count) in capture.extract().into_iter().flat_map(|(_, counts)| counts) {
+            match totals.iter_mut().find(|(t, _)| *t == time) {
+                Some(total) => total.1 += count,
+                None => totals.push((time, count)),
+            }
+        }
+    }
+    totals.sort();
+    for (time, count) in totals.iter() {
+        println!("counted {} records at {}", count, time);
+    }
+    totals
+}
+
+pub fn run() {
+    let mut params = Params::new("custom_timestamps");
+    let window = params.get("window", 100u64);
+    // records sent at each timestamp
+    let records = params.get("records", 3u64);
+    params.check(window > 0, "`window` must be positive");
+    params.validate();
+    let batch = |n: u64| (n * records..(n + 1) * records).collect::<Vec<_>>();
+
+    // wall-clock timestamps: records are stamped with the start of their window
+    let start = Millis::now().window(window);
+    let later = Millis(start.0 + window);
+    println!("{} joined with {} is {}", start, later, start.join(&later));
+    println!("{} delayed by {} is {}", start, Millis(30), Millis(30).results_in(&start).unwrap());
+    simulate(vec![(start, batch(0)), (later, batch(1))]);
+
+    // (epoch, sequence) timestamps: (1, 0) comes after (0, 7)
+    let (first, second) = (EpochSeq::new(0, 7), EpochSeq::new(1, 0));
+    println!("{} is before {}: {}", first, second, first.less_than(&second));
+    println!("{} met with {} is {}", first, second, first.meet(&second));
+    let next_epoch = EpochSeqSummary { epochs: 1, seqs: 0 };
+    println!("the next epoch after {} is {}", first, next_epoch.results_in(&first).unwrap());
+    simulate(vec![
+        (EpochSeq::new(0, 0), batch(0)),
+        (EpochSeq::new(0, 1), batch(1)),
+        (EpochSeq::new(1, 0), batch(2)),
+    ]);
+}
+
+#[cfg(test)]
+mod tests {
+    use timely::order::PartialOrder;
+    use timely::progress::timestamp::PathSummary;
+
+    use super::*;
+
+    #[test]
+    fn millis_are_a_lattice() {
+        let (start, later) = (Millis(1_000), Millis(1_100));
+        assert!(start.less_than(&later));
+        assert!(!later.less_equal(&start));
+        assert_eq!(start.join(&later), later);
+        assert_eq!(start.meet(&later), start);
+        assert_eq!(start.join(&start), start);
+    }
+
+    #[test]
+    fn millis_summaries_delay() {
+        assert_eq!(Millis(30).results_in(&Millis(1_000)), Some(Millis(1_030)));
+        assert_eq!(Millis(30).followed_by(&Millis(20)), Some(Millis(50)));
+        assert_eq!(Millis(1).results_in(&Millis(u64::MAX)), None);
+        assert_eq!(Millis(1).followed_by(&Millis(u64::MAX)), None);
+    }
+
+    #[test]
+    fn millis_windows() {
+        assert_eq!(Millis(1_234).window(100), Millis(1_200));
+        assert_eq!(Millis(1_200).window(100), Millis(1_200));
+        assert_eq!(Millis(1_234).window(1), Millis(1_234));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 1ms")]
+    fn empty_windows_are_rejected() {
+        Millis(1_234).window(0);
+    }
+
+    #[test]
+    fn epoch_seqs_are_lexicographic() {
+        let (first, second) = (EpochSeq::new(0, 7), EpochSeq::new(1, 0));
+        assert!(first.less_than(&second));
+        assert!(EpochSeq::new(1, 0).less_than(&EpochSeq::new(1, 1)));
+        assert_eq!(first.meet(&second), first);
+        assert_eq!(first.join(&second), second);
+    }
+
+    #[test]
+    fn epoch_seq_summaries() {
+        let next_epoch = EpochSeqSummary { epochs: 1, seqs: 0 };
+        let next_seq = EpochSeqSummary { epochs: 0, seqs: 1 };
+        assert_eq!(next_seq.results_in(&EpochSeq::new(0, 7)), Some(EpochSeq::new(0, 8)));
+        // a later epoch restarts the sequence
+        assert_eq!(next_epoch.results_in(&EpochSeq::new(0, 7)), Some(EpochSeq::new(1, 0)));
+        assert_eq!(next_seq.followed_by(&next_epoch), Some(next_epoch));
+        assert_eq!(next_epoch.followed_by(&next_seq), Some(EpochSeqSummary { epochs: 1, seqs: 1 }));
+        assert!(next_seq.less_equal(&next_epoch));
+        assert_eq!(next_seq.results_in(&EpochSeq::new(0, u64::MAX)), None);
+    }
+
+    #[test]
+    fn counts_per_millis() {
+        let (start, later) = (Millis(1_000), Millis(1_100));
+        let counts = simulate(vec![(start, (0..5).collect()), (later, (5..8).collect())]);
+        assert_eq!(counts, vec![(start, 5), (later, 3)]);
+    }
+
+    #[test]
+    fn counts_per_epoch_seq() {
+        let counts = simulate(vec![
+            (EpochSeq::new(0, 0), vec![1, 2]),
+            (EpochSeq::new(0, 1), vec![3]),
+            (EpochSeq::new(1, 0), vec![4, 5, 6]),
+        ]);
+        assert_eq!(counts, vec![
+            (EpochSeq::new(0, 0), 2),
+            (EpochSeq::new(0, 1), 1),
+            (EpochSeq::new(1, 0), 3),
+        ]);
+    }
+}
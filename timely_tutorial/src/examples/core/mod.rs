@@ -1,3 +1,5 @@
 pub mod dataflow;
 pub mod timestamps;
 pub mod progress;
+pub mod simulation;
+pub mod custom_timestamps;
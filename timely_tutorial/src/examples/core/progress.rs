@@ -1,13 +1,30 @@
 #![allow(unused_variables)]
 extern crate timely;
 
+use std::thread;
+use std::time::Duration;
+
 use timely::dataflow::InputHandle;
 use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
 
+use crate::examples::dashboard::Dashboard;
+use crate::examples::params::Params;
+use crate::examples::runner;
+
 pub fn run() {
-    timely::execute_from_args(std::env::args(), |worker| {
+    let mut params = Params::new("progress");
+    let rounds = params.get("rounds", 10u64);
+    // `dashboard=true` shows the progress of the workers instead of the records they see,
+    // sleeping `pause` milliseconds between rounds to make it watchable
+    let dashboard = params.get("dashboard", false);
+    let pause = params.get("pause", if dashboard { 200 } else { 0 });
+    params.validate();
+    let view = Dashboard::new(Duration::from_millis(100));
+
+    runner::execute_from_args(std::env::args(), move |worker| {
         let index = worker.index();
         let mut input = InputHandle::new();
+        let mut view = if dashboard { Some(view.attach(worker)) } else { None };
 
         // putting a probe after after inspect tells us whether
         // we should expect to see the **method associated with inspect fire again**
@@ -15,11 +32,11 @@ pub fn run() {
         let probe = worker.dataflow(|scope|
             scope.input_from(&mut input)
                 .exchange(|x| *x)
-                .inspect(move |x| println!("worker {}:\thello {}", index, x))
+                .inspect(move |x| if !dashboard { println!("worker {}:\thello {}", index, x) })
                 .probe()
         );
 
-        for round in 0..10 {
+        for round in 0..rounds {
             if worker.index() == 0 {
                 input.send(round);
             }
@@ -37,7 +54,17 @@ pub fn run() {
             // progress info is passive --> we see what is state of system
             //  - that means the implementation can choose to synchronize using the data probes
             //    shared in the system
-            worker.step_while(|| probe.less_than(input.time()));
+            match view.as_mut() {
+                Some(view) => while probe.less_than(input.time()) {
+                    worker.step();
+                    view.update(worker, *input.time(), &probe);
+                },
+                None => worker.step_while(|| probe.less_than(input.time())),
+            }
+            thread::sleep(Duration::from_millis(pause));
+        }
+        if let Some(view) = view.as_mut() {
+            view.finish(worker, *input.time(), &probe);
         }
     }).unwrap();
 }
@@ -0,0 +1,103 @@
+extern crate timely;
+
+use timely::dataflow::{InputHandle, ProbeHandle};
+use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
+
+use crate::examples::params::Params;
+use crate::examples::simulation::{Simulation, Trace};
+
+// A variant of the `progress` dataflow, run as a simulation: in each round every one of the
+// `workers=<n>` logical workers sends a few records, which are exchanged, so several workers have
+// records to send and to receive at once. `seed=<n>` picks the order in which the workers step,
+// and with it the order of the lines printed; the same seed always prints the same lines.
+pub fn run() {
+    let mut params = Params::new("simulation");
+    let seed = params.get("seed", 42);
+    let workers = params.get("workers", 3);
+    let rounds = params.get("rounds", 10);
+    params.check(workers > 0, "`workers` must be positive");
+    params.validate();
+
+    simulate(workers, rounds, seed);
+}
+
+// records each worker sends per round
+const RECORDS: u64 = 2;
+
+fn simulate(workers: usize, rounds: u64, seed: u64) -> Vec<String> {
+    let mut simulation = Simulation::new(workers, seed);
+    let trace = Trace::default();
+
+    // each worker builds the dataflow and hands back its input and probe, so that the driver
+    // below plays the role of the per-worker closure
+    let mut handles = simulation.dataflow(|index, scope| {
+        let trace = trace.clone();
+        let mut input = InputHandle::new();
+        let mut probe = ProbeHandle::new();
+        scope.input_from(&mut input)
+            .exchange(|(x, _)| *x)
+            .inspect(move |(x, from)| trace.record(format!("worker {}:\thello {} from {}", index, x, from)))
+            .probe_with(&mut probe);
+        (input, probe)
+    });
+
+    for round in 0..rounds {
+        for (index, (input, _)) in handles.iter_mut().enumerate() {
+            for record in 0..RECORDS {
+                input.send((round * 100 + index as u64 * RECORDS + record, index));
+            }
+            input.advance_to(round + 1);
+        }
+        simulation.step_while(|| handles.iter().any(|(input, probe)| probe.less_than(input.time())));
+    }
+
+    // closing the inputs lets the dataflows complete
+    handles.clear();
+    simulation.run();
+
+    trace.lines()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::simulate;
+
+    #[test]
+    fn a_seed_pins_the_trace() {
+        // the records of each round arrive in an order the seed picked
+        let expected = [
+            "worker 0:\thello 0 from 0",
+            "worker 0:\thello 3 from 1",
+            "worker 1:\thello 1 from 0",
+            "worker 1:\thello 4 from 2",
+            "worker 2:\thello 2 from 1",
+            "worker 2:\thello 5 from 2",
+            "worker 1:\thello 100 from 0",
+            "worker 1:\thello 103 from 1",
+            "worker 0:\thello 102 from 1",
+            "worker 0:\thello 105 from 2",
+            "worker 2:\thello 101 from 0",
+            "worker 2:\thello 104 from 2",
+        ];
+        assert_eq!(simulate(3, 2, 7), expected);
+    }
+
+    #[test]
+    fn seeds_pick_different_interleavings() {
+        let traces = (0..8).map(|seed| simulate(3, 3, seed)).collect::<Vec<_>>();
+        let mut distinct = traces.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert!(distinct.len() > 1, "all seeds gave the same trace");
+        assert_ne!(traces[0], traces[1]);
+
+        // the same records arrive, in whichever order
+        for trace in traces.iter() {
+            let mut sorted = trace.clone();
+            sorted.sort();
+            let mut first = traces[0].clone();
+            first.sort();
+            assert_eq!(sorted, first);
+        }
+    }
+}
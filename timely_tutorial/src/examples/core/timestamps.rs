@@ -4,8 +4,15 @@ extern crate timely;
 use timely::dataflow::InputHandle;
 use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
 
+use crate::examples::params::Params;
+use crate::examples::runner;
+
 pub fn run() {
-    timely::execute_from_args(std::env::args(), |worker| {
+    let mut params = Params::new("timestamps");
+    let rounds = params.get("rounds", 10u64);
+    params.validate();
+
+    runner::execute_from_args(std::env::args(), move |worker| {
         let index = worker.index();
         let mut input = InputHandle::new();
 
@@ -25,7 +32,7 @@ pub fn run() {
         );
 
         // introduce new data
-        for round in 0..10 {
+        for round in 0..rounds {
             if worker.index() == 0 {
                 input.send(round);
             }
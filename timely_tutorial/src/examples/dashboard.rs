@@ -0,0 +1,223 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use timely::communication::Allocate;
+use timely::dataflow::ProbeHandle;
+use timely::logging::TimelyEvent;
+use timely::worker::Worker;
+
+// A live view of the progress of a computation, redrawn in the terminal while it runs:
+//   - for each worker, the time of its input, the frontier of its probe, and the lag between
+//     them, in timestamps
+//   - for each operator, the records it received on each worker, counted from timely's logging
+//
+// Each worker publishes its status after stepping; the first worker draws the statuses of all
+// the workers of the process, and once they all finished, draws their final statuses. Timely's
+// logging is the only way to count records per operator; the dashboard takes over the `timely`
+// events and hands them on to any logger installed before it, e.g. the one of `graph`.
+#[derive(Clone)]
+pub struct Dashboard {
+    // the workers of the process, by index
+    workers: Arc<Mutex<BTreeMap<usize, Published>>>,
+    refresh: Duration,
+}
+
+#[derive(Clone, Default)]
+struct Published {
+    // `None` until the worker first publishes
+    status: Option<Status>,
+    finished: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Status {
+    input: u64,
+    frontier: Vec<u64>,
+    // records received by each operator, by operator address
+    records: Vec<(Vec<usize>, String, usize)>,
+}
+
+// operators and channels as logged by one worker, with the records received on each channel
+#[derive(Default)]
+struct Counts {
+    operators: HashMap<Vec<usize>, String>,
+    targets: HashMap<usize, Vec<usize>>,
+    received: HashMap<usize, usize>,
+}
+
+impl Counts {
+    // (address, name, records) of each operator that received records, by address
+    fn records(&self) -> Vec<(Vec<usize>, String, usize)> {
+        let mut records = HashMap::<Vec<usize>, usize>::new();
+        for (channel, length) in self.received.iter() {
+            if let Some(addr) = self.targets.get(channel) {
+                *records.entry(addr.clone()).or_insert(0) += length;
+            }
+        }
+        let mut records = records.into_iter()
+            .map(|(addr, count)| {
+                let name = self.operators.get(&addr).cloned().unwrap_or_default();
+                (addr, name, count)
+            })
+            .collect::<Vec<_>>();
+        records.sort();
+        records
+    }
+}
+
+pub struct WorkerView {
+    index: usize,
+    dashboard: Dashboard,
+    counts: Rc<RefCell<Counts>>,
+    // when the first worker last drew the view
+    drawn: Option<Instant>,
+}
+
+impl Dashboard {
+    pub fn new(refresh: Duration) -> Dashboard {
+        Dashboard { workers: Arc::new(Mutex::new(BTreeMap::new())), refresh }
+    }
+
+    // installs the logger counting records; call it before building the dataflows to watch
+    pub fn attach<A: Allocate>(&self, worker: &mut Worker<A>) -> WorkerView {
+        let counts = Rc::new(RefCell::new(Counts::default()));
+        let logged = counts.clone();
+        let mut previous = worker.log_register().get::<TimelyEvent>("timely");
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+            let mut counts = logged.borrow_mut();
+            for (_, _, event) in data.drain(..) {
+                match event {
+                    TimelyEvent::Operates(ref event) => {
+                        counts.operators.insert(event.addr.clone(), event.name.clone());
+                    },
+                    // index 0 is the scope itself, i.e. the channel leaves the scope
+                    TimelyEvent::Channels(ref event) if event.target.0 != 0 => {
+                        let mut addr = event.scope_addr.clone();
+                        addr.push(event.target.0);
+                        counts.targets.insert(event.id, addr);
+                    },
+                    TimelyEvent::Messages(ref event) if !event.is_send => {
+                        *counts.received.entry(event.channel).or_insert(0) += event.length;
+                    },
+                    _ => (),
+                }
+                if let Some(previous) = previous.as_ref() {
+                    previous.log(event);
+                }
+            }
+            if let Some(previous) = previous.as_mut() {
+                previous.flush();
+            }
+        });
+
+        self.workers.lock().unwrap().insert(worker.index(), Published::default());
+        WorkerView {
+            index: worker.index(),
+            dashboard: self.clone(),
+            counts,
+            drawn: None,
+        }
+    }
+}
+
+impl WorkerView {
+    // publishes the status of the worker, after it stepped; the first worker redraws the view
+    // once `refresh` has passed since it last did
+    pub fn update<A: Allocate>(&mut self, worker: &mut Worker<A>, input: u64, probe: &ProbeHandle<u64>) {
+        self.publish(worker, input, probe, false);
+        if self.index == 0 && self.drawn.is_none_or(|drawn| drawn.elapsed() >= self.dashboard.refresh) {
+            self.draw();
+        }
+    }
+
+    // Publishes the last status of the worker. The first worker then waits for the last status of
+    // every other worker before drawing them; it keeps stepping meanwhile, since the others may
+    // need its progress to finish.
+    pub fn finish<A: Allocate>(&mut self, worker: &mut Worker<A>, input: u64, probe: &ProbeHandle<u64>) {
+        self.publish(worker, input, probe, true);
+        if self.index == 0 {
+            while !self.dashboard.workers.lock().unwrap().values().all(|published| published.finished) {
+                worker.step();
+                thread::sleep(Duration::from_millis(1));
+            }
+            self.draw();
+        }
+    }
+
+    fn publish<A: Allocate>(&mut self, worker: &mut Worker<A>, input: u64, probe: &ProbeHandle<u64>, finished: bool) {
+        // logged events are buffered; flushing hands them to our logger
+        worker.log_register().flush();
+
+        let status = Status {
+            input,
+            frontier: probe.with_frontier(|frontier| frontier.iter().cloned().collect()),
+            records: self.counts.borrow().records(),
+        };
+        let published = Published { status: Some(status), finished };
+        self.dashboard.workers.lock().unwrap().insert(self.index, published);
+    }
+
+    fn draw(&mut self) {
+        self.drawn = Some(Instant::now());
+        let workers = self.dashboard.workers.lock().unwrap().clone();
+        // clear the terminal and move the cursor to the top left corner
+        print!("\x1b[2J\x1b[H{}", render(&workers));
+    }
+}
+
+fn render(workers: &BTreeMap<usize, Published>) -> String {
+    let mut out = String::new();
+    writeln!(out, "{:<8} {:>8} {:>12} {:>6}", "worker", "input", "probe", "lag").unwrap();
+    for (index, published) in workers.iter() {
+        let status = match published.status.as_ref() {
+            Some(status) => status,
+            None => {
+                writeln!(out, "{:<8} {:>8} {:>12} {:>6}", index, "", "starting", "").unwrap();
+                continue;
+            },
+        };
+        // an empty frontier means the probe will see no more timestamps
+        let (frontier, lag) = match status.frontier.iter().min() {
+            Some(min) => (format!("{:?}", status.frontier), status.input.saturating_sub(*min)),
+            None => ("done".to_owned(), 0),
+        };
+        writeln!(out, "{:<8} {:>8} {:>12} {:>6}", index, status.input, frontier, lag).unwrap();
+    }
+    let statuses = workers.values().filter_map(|published| published.status.as_ref()).collect::<Vec<_>>();
+
+    // operators are the same on all workers, but some may not have received records yet; the
+    // first index of an address is the worker, so it is left out when comparing them
+    let mut operators = statuses.iter()
+        .flat_map(|status| status.records.iter().map(|(addr, name, _)| (addr[1..].to_vec(), name.clone())))
+        .collect::<Vec<_>>();
+    operators.sort();
+    operators.dedup();
+
+    writeln!(out).unwrap();
+    write!(out, "{:<24}", "records received by").unwrap();
+    for (index, published) in workers.iter() {
+        if published.status.is_some() {
+            write!(out, " {:>10}", format!("worker {}", index)).unwrap();
+        }
+    }
+    writeln!(out).unwrap();
+    for (addr, name) in operators {
+        let path = addr.iter().map(|index| index.to_string()).collect::<Vec<_>>().join(".");
+        write!(out, "{:<24}", format!("{} ({})", name, path)).unwrap();
+        for status in statuses.iter() {
+            let count = status.records.iter()
+                .find(|(other, _, _)| other[1..] == addr[..])
+                .map_or(0, |(_, _, count)| *count);
+            write!(out, " {:>10}", count).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    out
+}
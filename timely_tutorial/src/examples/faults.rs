@@ -0,0 +1,160 @@
+extern crate timely;
+
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use timely::Data;
+use timely::communication::allocator::Process;
+use timely::dataflow::{Scope, Stream};
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Capability, Capture, Operator};
+use timely::dataflow::operators::capture::Extract;
+use timely::dataflow::scopes::Child;
+use timely::worker::Worker;
+
+use crate::examples::simulation::Simulation;
+
+// An operator should give the same output whatever the way its input arrives, as long as the
+// timestamps of the records stay the same. The faults below change only the way, so an operator
+// whose output changes under them depends on something timely does not promise.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Faults {
+    // batches are held back for up to this many activations
+    pub max_delay: usize,
+    // batches are released in pieces, in separate activations
+    pub split: bool,
+    // batches held back together are released in any order, including batches of later times
+    // before batches of earlier times
+    pub reorder: bool,
+    // capabilities for the times of the batches are also held for up to this many activations
+    // after the batch itself was released, delaying the frontier
+    pub hold: usize,
+}
+
+impl Faults {
+    pub fn all() -> Faults {
+        Faults { max_delay: 3, split: true, reorder: true, hold: 3 }
+    }
+}
+
+// Inserted by the code under test on the streams to perturb; each stream gets its own generator
+// state, derived from the seed of the run, the worker and the order of the `inject` calls.
+#[derive(Clone)]
+pub struct Injector {
+    faults: Faults,
+    seed: u64,
+    streams: Rc<Cell<u64>>,
+}
+
+impl Injector {
+    fn new(faults: Faults, seed: u64, index: usize) -> Injector {
+        let seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(index as u64 + 1);
+        Injector { faults, seed, streams: Rc::new(Cell::new(0)) }
+    }
+
+    pub fn inject<G: Scope, D: Data>(&self, stream: &Stream<G, D>) -> Stream<G, D> {
+        let faults = self.faults;
+        let stream_index = self.streams.get();
+        self.streams.set(stream_index + 1);
+        let mut state = (self.seed ^ (stream_index + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15)).max(1);
+        let mut random = move |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % (bound as u64 + 1)) as usize
+        };
+
+        stream.unary_frontier(Pipeline, "InjectFaults", move |_capability, _info| {
+            // (capability, records, activations left before the release)
+            let mut pending = Vec::<(Capability<G::Timestamp>, Vec<D>, usize)>::new();
+            // (extra capability, activations left before it is dropped)
+            let mut holds = Vec::<(Capability<G::Timestamp>, usize)>::new();
+
+            move |input, output| {
+                while let Some((time, data)) = input.next() {
+                    let capability = time.retain();
+                    if faults.hold > 0 {
+                        holds.push((capability.clone(), random(faults.hold)));
+                    }
+                    pending.push((capability, data.replace(Vec::new()), random(faults.max_delay)));
+                }
+
+                let mut ready = Vec::new();
+                let mut index = 0;
+                while index < pending.len() {
+                    if pending[index].2 == 0 {
+                        ready.push(pending.remove(index));
+                    } else {
+                        pending[index].2 -= 1;
+                        index += 1;
+                    }
+                }
+                if faults.reorder {
+                    for i in (1..ready.len()).rev() {
+                        ready.swap(i, random(i));
+                    }
+                }
+
+                for (capability, mut records, _) in ready {
+                    // the rest of a split batch comes in a later activation, as its own batch
+                    if faults.split && records.len() > 1 {
+                        let rest = records.split_off(1 + random(records.len() - 2));
+                        pending.push((capability.clone(), rest, 1 + random(faults.max_delay)));
+                    }
+                    output.session(&capability).give_vec(&mut records);
+                }
+
+                holds.retain(|(_, left)| *left > 0);
+                for (_, left) in holds.iter_mut() {
+                    *left -= 1;
+                }
+            }
+        })
+    }
+}
+
+pub type Scope64<'a> = Child<'a, Worker<Process>, u64>;
+
+// the records of each time seen by each worker
+pub type Output<D> = Vec<Vec<(u64, Vec<D>)>>;
+
+// Runs the dataflow built by `build` once without faults and then once per seed with `faults`,
+// on `peers` simulated workers, comparing the records of each time at each worker with the
+// baseline. Returns the baseline, or the first difference found.
+pub fn check<D, F>(peers: usize, faults: Faults, seeds: u64, build: F) -> Result<Output<D>, String>
+where
+    D: Data+Ord+Debug,
+    F: for<'a> Fn(&mut Scope64<'a>, &Injector)->Stream<Scope64<'a>, D> {
+    let baseline = execute(peers, Faults::default(), 0, &build);
+    for seed in 0..seeds {
+        let output = execute(peers, faults, seed, &build);
+        for (index, (expected, actual)) in baseline.iter().zip(output.iter()).enumerate() {
+            if expected != actual {
+                return Err(format!("seed {}, worker {}: expected {:?}, got {:?}", seed, index, expected, actual));
+            }
+        }
+    }
+    Ok(baseline)
+}
+
+// the output of each worker, with the records of each time sorted
+fn execute<D, F>(peers: usize, faults: Faults, seed: u64, build: &F) -> Output<D>
+where
+    D: Data+Ord,
+    F: for<'a> Fn(&mut Scope64<'a>, &Injector)->Stream<Scope64<'a>, D> {
+    // the seed of the run also picks the order the workers step in
+    let mut simulation = Simulation::new(peers, seed + 1);
+    let captures = simulation.dataflow(|index, scope| {
+        build(scope, &Injector::new(faults, seed, index)).capture()
+    });
+    simulation.run();
+
+    captures.into_iter().map(|capture| {
+        let mut output = capture.extract();
+        for (_, records) in output.iter_mut() {
+            records.sort();
+        }
+        output
+    }).collect()
+}
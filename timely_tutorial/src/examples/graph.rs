@@ -0,0 +1,295 @@
+extern crate timely;
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use timely::logging::TimelyEvent;
+
+// Dataflow topology, as reported by timely's logging when a dataflow is constructed:
+//   - each operator has an address, the path of indices from the worker down to it; scopes
+//     (the dataflow itself, `iterative`, ...) contain other operators
+//   - each channel lives in a scope and connects (operator index, port) pairs of that scope; the
+//     index 0 stands for the scope itself, i.e. its inputs when used as a source and its outputs
+//     when used as a target
+#[derive(Clone, Debug)]
+pub struct Operator {
+    pub addr: Vec<usize>,
+    pub name: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Channel {
+    pub id: usize,
+    pub scope: Vec<usize>,
+    pub source: (usize, usize),
+    pub target: (usize, usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format { Dot, Mermaid }
+
+// one per computation, i.e. per `example`/`execute_from_args` call
+#[derive(Default, Debug)]
+pub struct Topology {
+    pub operators: Vec<Operator>,
+    pub channels: Vec<Channel>,
+    // channels on which records moved between workers
+    pub exchanged: HashSet<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Edge { Plain, Loop, Exchange }
+
+impl Topology {
+    pub fn record(&mut self, event: TimelyEvent) {
+        match event {
+            TimelyEvent::Operates(event) => {
+                self.operators.push(Operator { addr: event.addr, name: event.name });
+            },
+            TimelyEvent::Channels(event) => {
+                self.channels.push(Channel {
+                    id: event.id,
+                    scope: event.scope_addr,
+                    source: event.source,
+                    target: event.target,
+                });
+            },
+            // the pact of a channel is not logged, so exchanges are only visible once records
+            // actually cross workers, i.e. when running with more than one worker
+            TimelyEvent::Messages(ref event) if event.source != event.target => {
+                self.exchanged.insert(event.channel);
+            },
+            _ => (),
+        }
+    }
+
+    fn operator(&self, addr: &[usize]) -> Option<&Operator> {
+        self.operators.iter().find(|operator| operator.addr[..] == *addr)
+    }
+
+    fn is_scope(&self, addr: &[usize]) -> bool {
+        self.operators.iter().any(|operator| operator.addr.len() > addr.len() && operator.addr.starts_with(addr))
+    }
+
+    fn children<'a>(&'a self, addr: &'a [usize]) -> impl Iterator<Item=&'a Operator> {
+        self.operators.iter().filter(move |operator| {
+            operator.addr.len() == addr.len() + 1 && operator.addr.starts_with(addr)
+        })
+    }
+
+    // the node an endpoint of a channel in `scope` connects to; a nested scope is drawn as a
+    // cluster, so its ports become separate nodes inside of it
+    fn endpoint(&self, prefix: &str, scope: &[usize], (index, port): (usize, usize), source: bool) -> String {
+        if index == 0 {
+            let side = if source { "in" } else { "out" };
+            return format!("{}_{}{}", id(prefix, scope), side, port);
+        }
+        let mut addr = scope.to_vec();
+        addr.push(index);
+        if self.is_scope(&addr) {
+            let side = if source { "out" } else { "in" };
+            format!("{}_{}{}", id(prefix, &addr), side, port)
+        } else {
+            id(prefix, &addr)
+        }
+    }
+
+    fn edge(&self, channel: &Channel) -> Edge {
+        let mut source = channel.scope.clone();
+        source.push(channel.source.0);
+        match self.operator(&source) {
+            Some(operator) if operator.name == "Feedback" => Edge::Loop,
+            _ if self.exchanged.contains(&channel.id) => Edge::Exchange,
+            _ => Edge::Plain,
+        }
+    }
+
+    // the ports of `scope` used by its channels, as (node, label) pairs
+    fn ports(&self, prefix: &str, scope: &[usize]) -> Vec<(String, String)> {
+        let mut ports = Vec::new();
+        for channel in self.channels.iter().filter(|channel| channel.scope[..] == *scope) {
+            if channel.source.0 == 0 {
+                ports.push((self.endpoint(prefix, scope, channel.source, true), format!("in {}", channel.source.1)));
+            }
+            if channel.target.0 == 0 {
+                ports.push((self.endpoint(prefix, scope, channel.target, false), format!("out {}", channel.target.1)));
+            }
+        }
+        ports.sort();
+        ports.dedup();
+        ports
+    }
+
+    fn dot_scope(&self, out: &mut String, prefix: &str, scope: &Operator, depth: usize) {
+        let indent = "  ".repeat(depth);
+        writeln!(out, "{}subgraph cluster_{} {{", indent, id(prefix, &scope.addr)).unwrap();
+        writeln!(out, "{}  label=\"{}\";", indent, escape(&scope.name)).unwrap();
+        for (node, label) in self.ports(prefix, &scope.addr) {
+            writeln!(out, "{}  {} [label=\"\", shape=point, xlabel=\"{}\"];", indent, node, label).unwrap();
+        }
+        for child in self.children(&scope.addr) {
+            if self.is_scope(&child.addr) {
+                self.dot_scope(out, prefix, child, depth + 1);
+            } else {
+                let shape = if child.name == "Exchange" { "diamond" } else { "box" };
+                writeln!(out, "{}  {} [label=\"{}\", shape={}];", indent, id(prefix, &child.addr), escape(&child.name), shape).unwrap();
+            }
+        }
+        writeln!(out, "{}}}", indent).unwrap();
+    }
+
+    fn mermaid_scope(&self, out: &mut String, prefix: &str, scope: &Operator, depth: usize) {
+        let indent = "  ".repeat(depth);
+        writeln!(out, "{}subgraph {} [\"{}\"]", indent, id(prefix, &scope.addr), escape(&scope.name)).unwrap();
+        for (node, label) in self.ports(prefix, &scope.addr) {
+            writeln!(out, "{}  {}((\"{}\"))", indent, node, label).unwrap();
+        }
+        for child in self.children(&scope.addr) {
+            if self.is_scope(&child.addr) {
+                self.mermaid_scope(out, prefix, child, depth + 1);
+            } else if child.name == "Exchange" {
+                writeln!(out, "{}  {}{{{{\"{}\"}}}}", indent, id(prefix, &child.addr), escape(&child.name)).unwrap();
+            } else {
+                writeln!(out, "{}  {}[\"{}\"]", indent, id(prefix, &child.addr), escape(&child.name)).unwrap();
+            }
+        }
+        writeln!(out, "{}end", indent).unwrap();
+    }
+
+    // dataflows are the scopes at the root; addresses start with the worker index and the
+    // dataflows themselves are not reported as operators
+    fn dataflows(&self, computation: usize) -> Vec<Operator> {
+        let mut roots = self.operators.iter()
+            .filter(|operator| operator.addr.len() > 2)
+            .map(|operator| operator.addr[..2].to_vec())
+            .collect::<Vec<_>>();
+        // operators of different dataflows may be logged interleaved
+        roots.sort();
+        roots.dedup();
+        roots.into_iter().map(|addr| {
+            let name = format!("computation {}, dataflow {}", computation, addr[1]);
+            Operator { addr, name }
+        }).collect()
+    }
+}
+
+/// Renders the topologies of several computations as a single graph.
+pub fn render(topologies: &[Topology], format: Format) -> String {
+    let mut out = String::new();
+    match format {
+        Format::Dot => writeln!(out, "digraph {{").unwrap(),
+        Format::Mermaid => writeln!(out, "flowchart TD").unwrap(),
+    }
+
+    for (computation, topology) in topologies.iter().enumerate() {
+        let prefix = format!("c{}", computation);
+        for dataflow in topology.dataflows(computation) {
+            match format {
+                Format::Dot => topology.dot_scope(&mut out, &prefix, &dataflow, 1),
+                Format::Mermaid => topology.mermaid_scope(&mut out, &prefix, &dataflow, 1),
+            }
+        }
+        for channel in topology.channels.iter() {
+            let source = topology.endpoint(&prefix, &channel.scope, channel.source, true);
+            let target = topology.endpoint(&prefix, &channel.scope, channel.target, false);
+            let line = match (format, topology.edge(channel)) {
+                (Format::Dot, Edge::Plain) => format!("{} -> {};", source, target),
+                (Format::Dot, Edge::Loop) => format!("{} -> {} [style=dashed, label=\"loop\"];", source, target),
+                (Format::Dot, Edge::Exchange) => format!("{} -> {} [style=bold, label=\"exchange\"];", source, target),
+                (Format::Mermaid, Edge::Plain) => format!("{} --> {}", source, target),
+                (Format::Mermaid, Edge::Loop) => format!("{} -. loop .-> {}", source, target),
+                (Format::Mermaid, Edge::Exchange) => format!("{} == exchange ==> {}", source, target),
+            };
+            writeln!(out, "  {}", line).unwrap();
+        }
+    }
+
+    if format == Format::Dot {
+        writeln!(out, "}}").unwrap();
+    }
+    out
+}
+
+fn id(prefix: &str, addr: &[usize]) -> String {
+    let path = addr.iter().map(|index| index.to_string()).collect::<Vec<_>>();
+    format!("{}_{}", prefix, path.join("_"))
+}
+
+fn escape(name: &str) -> String {
+    name.replace('"', "'")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use timely::Configuration;
+    use timely::dataflow::operators::*;
+    use timely::logging::TimelyEvent;
+
+    use super::{render, Format, Topology};
+
+    // the topology recorded by the first worker of a dataflow with an exchange and a loop
+    fn topology(workers: usize) -> Topology {
+        timely::execute(Configuration::Process(workers), |worker| {
+            let topology = Rc::new(RefCell::new(Topology::default()));
+            if worker.index() == 0 {
+                let topology = topology.clone();
+                worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+                    for (_, _, event) in data.drain(..) {
+                        topology.borrow_mut().record(event);
+                    }
+                });
+            }
+            worker.dataflow::<u64,_,_>(|scope| {
+                let (handle, stream) = scope.feedback(1);
+                (0 .. 10u64)
+                    .to_stream(scope)
+                    .exchange(|x| *x)
+                    .concat(&stream)
+                    .map(|x| x / 2)
+                    .filter(|x| *x != 0)
+                    .connect_loop(handle);
+            });
+            while worker.step() {}
+            worker.log_register().flush();
+            let topology = topology.replace(Topology::default());
+            (worker.index(), topology)
+        }).unwrap().join().into_iter().map(Result::unwrap).find(|(index, _)| *index == 0).unwrap().1
+    }
+
+    #[test]
+    fn dot_marks_loops_and_exchanges() {
+        let dot = render(&[topology(2)], Format::Dot);
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("subgraph cluster_c0_0_0 {"));
+        assert!(dot.contains("[label=\"Feedback\", shape=box];"));
+        // the feedback feeds the concatenation, the exchange happens between `to_stream` and the
+        // exchange operator
+        assert!(dot.contains("c0_0_0_1 -> c0_0_0_4 [style=dashed, label=\"loop\"];"));
+        assert!(dot.contains("c0_0_0_2 -> c0_0_0_3 [style=bold, label=\"exchange\"];"));
+        assert!(dot.contains("c0_0_0_3 -> c0_0_0_4;"));
+    }
+
+    #[test]
+    fn mermaid_marks_loops_and_exchanges() {
+        let mermaid = render(&[topology(2)], Format::Mermaid);
+        assert!(mermaid.starts_with("flowchart TD\n"));
+        assert!(mermaid.contains("subgraph c0_0_0 [\"computation 0, dataflow 0\"]"));
+        assert!(mermaid.contains("c0_0_0_3{{\"Exchange\"}}"));
+        assert!(mermaid.contains("c0_0_0_1 -. loop .-> c0_0_0_4"));
+        assert!(mermaid.contains("c0_0_0_2 == exchange ==> c0_0_0_3"));
+        assert!(mermaid.contains("c0_0_0_3 --> c0_0_0_4"));
+    }
+
+    #[test]
+    fn exchanges_are_not_detected_on_a_single_worker() {
+        let topology = topology(1);
+        assert!(topology.exchanged.is_empty());
+        let dot = render(&[topology], Format::Dot);
+        assert!(dot.contains("c0_0_0_1 -> c0_0_0_4 [style=dashed, label=\"loop\"];"));
+        assert!(dot.contains("c0_0_0_2 -> c0_0_0_3;"));
+    }
+}
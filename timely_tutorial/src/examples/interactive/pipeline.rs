@@ -0,0 +1,282 @@
+extern crate timely;
+
+use std::fmt;
+
+use timely::dataflow::{Scope, Stream};
+use timely::dataflow::operators::{
+    ToStream, Inspect, Map, Filter, Partition, Concatenate, Exchange
+};
+
+// A small pipeline language, e.g. `range 0 5 | flat_map range | filter <3 | partition 3 | inspect`
+//   - the first stage is always a source: `range <from> <to>`
+//   - stages after it transform the stream(s) of u64 values, mirroring `operators.rs`
+//   - `partition` splits the stream into several logical streams; the stages after it apply to
+//     each of them until `concatenate` puts them back together
+pub const HELP: &str = "\
+stages (separated by `|`):
+  range <from> <to>     source emitting from..to; must come first
+  flat_map range        replace x by 0..x
+  filter <op><n>        keep x if `x <op> n`, op is one of < <= > >= == !=
+  map <op><n>           replace x by `x <op> n`, op is one of + - * / %
+  partition <n>         split into n streams by x % n, at most 64 streams in all
+  concatenate           merge partitioned streams
+  exchange              route records between workers by value
+  inspect               print each record";
+
+// Each stream is an operator on every worker; partitioning again multiplies the streams, so their
+// number is capped, as a large `partition <n>` would otherwise allocate until memory runs out.
+pub const MAX_STREAMS: u64 = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison { Less, LessEqual, Greater, GreaterEqual, Equal, NotEqual }
+
+impl Comparison {
+    fn holds(self, x: u64, n: u64) -> bool {
+        match self {
+            Comparison::Less => x < n,
+            Comparison::LessEqual => x <= n,
+            Comparison::Greater => x > n,
+            Comparison::GreaterEqual => x >= n,
+            Comparison::Equal => x == n,
+            Comparison::NotEqual => x != n,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Arithmetic { Add, Sub, Mul, Div, Rem }
+
+impl Arithmetic {
+    // arithmetic saturates instead of overflowing; division by zero is rejected by the parser
+    fn apply(self, x: u64, n: u64) -> u64 {
+        match self {
+            Arithmetic::Add => x.saturating_add(n),
+            Arithmetic::Sub => x.saturating_sub(n),
+            Arithmetic::Mul => x.saturating_mul(n),
+            Arithmetic::Div => x / n,
+            Arithmetic::Rem => x % n,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Stage {
+    FlatMapRange,
+    Filter(Comparison, u64),
+    Map(Arithmetic, u64),
+    Partition(u64),
+    Concatenate,
+    Exchange,
+    Inspect,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pipeline {
+    from: u64,
+    to: u64,
+    stages: Vec<Stage>,
+}
+
+// `stage` is the 1-based position of the offending stage in the pipeline
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub stage: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "stage {}: {}", self.stage, self.message)
+    }
+}
+
+impl Pipeline {
+    pub fn parse(text: &str) -> Result<Pipeline, ParseError> {
+        let mut parts = text.split('|').map(|part| part.split_whitespace().collect::<Vec<_>>());
+
+        let (from, to) = match parts.next() {
+            Some(ref words) if words.first() == Some(&"range") => {
+                if words.len() != 3 {
+                    return Err(error(1, "expected `range <from> <to>`"));
+                }
+                (number(1, words[1])?, number(1, words[2])?)
+            },
+            _ => return Err(error(1, "a pipeline must start with `range <from> <to>`")),
+        };
+
+        let mut stages = Vec::new();
+        // the number of streams the stages so far leave
+        let mut streams = 1u64;
+        for (position, words) in parts.enumerate() {
+            // the source is stage 1
+            let position = position + 2;
+            let stage = match words.as_slice() {
+                ["flat_map", "range"] => Stage::FlatMapRange,
+                // `filter <3` and `filter < 3` are both accepted
+                ["filter", condition @ ..] if !condition.is_empty() => {
+                    let (op, n) = operator(position, &condition.concat(), &["<=", ">=", "==", "!=", "<", ">"])?;
+                    let comparison = match op {
+                        "<" => Comparison::Less,
+                        "<=" => Comparison::LessEqual,
+                        ">" => Comparison::Greater,
+                        ">=" => Comparison::GreaterEqual,
+                        "==" => Comparison::Equal,
+                        _ => Comparison::NotEqual,
+                    };
+                    Stage::Filter(comparison, n)
+                },
+                ["map", expression @ ..] if !expression.is_empty() => {
+                    let (op, n) = operator(position, &expression.concat(), &["+", "-", "*", "/", "%"])?;
+                    let arithmetic = match op {
+                        "+" => Arithmetic::Add,
+                        "-" => Arithmetic::Sub,
+                        "*" => Arithmetic::Mul,
+                        "/" => Arithmetic::Div,
+                        _ => Arithmetic::Rem,
+                    };
+                    if n == 0 && (arithmetic == Arithmetic::Div || arithmetic == Arithmetic::Rem) {
+                        return Err(error(position, "division by zero"));
+                    }
+                    Stage::Map(arithmetic, n)
+                },
+                ["partition", parts] => {
+                    let parts = number(position, parts)?;
+                    if parts == 0 {
+                        return Err(error(position, "cannot partition into 0 streams"));
+                    }
+                    streams = match streams.checked_mul(parts) {
+                        Some(streams) if streams <= MAX_STREAMS => streams,
+                        _ => return Err(error(position, &format!("at most {} streams, partitioned or not", MAX_STREAMS))),
+                    };
+                    Stage::Partition(parts)
+                },
+                ["concatenate"] => {
+                    if streams == 1 {
+                        return Err(error(position, "`concatenate` needs a `partition` before it"));
+                    }
+                    streams = 1;
+                    Stage::Concatenate
+                },
+                ["exchange"] => Stage::Exchange,
+                ["inspect"] => Stage::Inspect,
+                ["range", ..] => return Err(error(position, "`range` can only be the first stage")),
+                [] => return Err(error(position, "empty stage")),
+                _ => return Err(error(position, &format!("unknown stage `{}`", words.join(" ")))),
+            };
+            stages.push(stage);
+        }
+
+        Ok(Pipeline { from, to, stages })
+    }
+
+    // builds the dataflow in `scope`; `index` is only used to label the inspected records
+    pub fn build<G: Scope>(&self, scope: &mut G, index: usize) {
+        let mut streams: Vec<Stream<G, u64>> = vec![(self.from .. self.to).to_stream(scope)];
+
+        for stage in self.stages.iter().cloned() {
+            streams = match stage {
+                Stage::FlatMapRange => streams.iter().map(|s| s.flat_map(|x| 0 .. x)).collect(),
+                Stage::Filter(op, n) => streams.iter().map(|s| s.filter(move |x| op.holds(*x, n))).collect(),
+                Stage::Map(op, n) => streams.iter().map(|s| s.map(move |x| op.apply(x, n))).collect(),
+                Stage::Partition(parts) => {
+                    streams.iter().flat_map(|s| s.partition(parts, move |x| (x % parts, x))).collect()
+                },
+                Stage::Concatenate => vec![scope.concatenate(streams)],
+                Stage::Exchange => streams.iter().map(|s| s.exchange(|x| *x)).collect(),
+                Stage::Inspect => {
+                    let partitioned = streams.len() > 1;
+                    streams.iter().enumerate().map(|(i, s)| {
+                        s.inspect(move |x| {
+                            if partitioned {
+                                println!("worker {} partition {}: {}", index, i, x)
+                            } else {
+                                println!("worker {}: {}", index, x)
+                            }
+                        })
+                    }).collect()
+                },
+            };
+        }
+    }
+}
+
+fn error(stage: usize, message: &str) -> ParseError {
+    ParseError { stage, message: message.to_owned() }
+}
+
+fn number(stage: usize, text: &str) -> Result<u64, ParseError> {
+    text.parse().map_err(|_| error(stage, &format!("`{}` is not a number", text)))
+}
+
+// splits e.g. `<=3` into `("<=", 3)`; longer operators must be listed first
+fn operator(stage: usize, text: &str, ops: &[&'static str]) -> Result<(&'static str, u64), ParseError> {
+    for op in ops.iter() {
+        if let Some(n) = text.strip_prefix(op) {
+            return Ok((op, number(stage, n)?));
+        }
+    }
+    Err(error(stage, &format!("`{}` should look like <op><number>, op is one of {}", text, ops.join(" "))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Arithmetic, Comparison, Pipeline, Stage, MAX_STREAMS};
+
+    // the message of the error parsing `text`, which must be about `stage`
+    fn error(text: &str, stage: usize) -> String {
+        let error = Pipeline::parse(text).unwrap_err();
+        assert_eq!(error.stage, stage, "{}", error);
+        error.message
+    }
+
+    #[test]
+    fn valid_stages() {
+        let pipeline = Pipeline::parse("range 0 5 | flat_map range | filter <3 | map * 2 | partition 3 | inspect | concatenate | exchange").unwrap();
+        assert_eq!((pipeline.from, pipeline.to), (0, 5));
+        assert_eq!(pipeline.stages, vec![
+            Stage::FlatMapRange,
+            Stage::Filter(Comparison::Less, 3),
+            Stage::Map(Arithmetic::Mul, 2),
+            Stage::Partition(3),
+            Stage::Inspect,
+            Stage::Concatenate,
+            Stage::Exchange,
+        ]);
+        assert_eq!(Pipeline::parse("  range 2 4  ").unwrap().stages, Vec::new());
+        assert_eq!(Pipeline::parse("range 0 1 | filter != 0").unwrap().stages, vec![Stage::Filter(Comparison::NotEqual, 0)]);
+    }
+
+    #[test]
+    fn unknown_stages() {
+        assert_eq!(error("range 0 5 | sort", 2), "unknown stage `sort`");
+        assert_eq!(error("range 0 5 | inspect | flat_map x", 3), "unknown stage `flat_map x`");
+        assert_eq!(error("range 0 5 || inspect", 2), "empty stage");
+        assert_eq!(error("range 0 5 | range 0 5", 2), "`range` can only be the first stage");
+        assert_eq!(error("inspect", 1), "a pipeline must start with `range <from> <to>`");
+        assert_eq!(error("range 0 5 | concatenate", 2), "`concatenate` needs a `partition` before it");
+    }
+
+    #[test]
+    fn bad_numbers() {
+        assert_eq!(error("range 0 x", 1), "`x` is not a number");
+        assert_eq!(error("range 0 -1", 1), "`-1` is not a number");
+        assert_eq!(error("range 0", 1), "expected `range <from> <to>`");
+        assert_eq!(error("range 0 5 | filter <", 2), "`` is not a number");
+        assert_eq!(error("range 0 5 | filter ~3", 2), "`~3` should look like <op><number>, op is one of <= >= == != < >");
+        assert_eq!(error("range 0 5 | map % 0", 2), "division by zero");
+        assert_eq!(error("range 0 5 | partition 0", 2), "cannot partition into 0 streams");
+        assert_eq!(error("range 0 5 | partition 99999999999999999999", 2), "`99999999999999999999` is not a number");
+    }
+
+    #[test]
+    fn partitions_are_capped() {
+        let cap = format!("at most {} streams, partitioned or not", MAX_STREAMS);
+        assert!(Pipeline::parse(&format!("range 0 5 | partition {}", MAX_STREAMS)).is_ok());
+        assert_eq!(error(&format!("range 0 5 | partition {}", MAX_STREAMS + 1), 2), cap);
+        assert_eq!(error("range 0 5 | partition 18446744073709551615", 2), cap);
+        // partitioning partitioned streams multiplies them, and does not overflow
+        assert_eq!(error("range 0 5 | partition 8 | partition 16", 3), cap);
+        assert_eq!(error("range 0 5 | partition 64 | partition 18446744073709551615", 3), cap);
+        assert!(Pipeline::parse("range 0 5 | partition 8 | partition 8 | concatenate | partition 64").is_ok());
+    }
+}
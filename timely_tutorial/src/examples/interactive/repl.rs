@@ -0,0 +1,111 @@
+extern crate timely;
+
+use std::io::{self, BufRead, Write};
+
+use crate::examples::interactive::pipeline::{Pipeline, HELP};
+use crate::examples::params::Params;
+use crate::examples::runner;
+
+// Reads pipelines from stdin and runs each of them as a new timely computation, so the usual
+// `-w`/`-n`/`-p` arguments apply to every pipeline, e.g.
+//   > range 0 5 | flat_map range | filter <3 | partition 3 | inspect
+pub fn run() {
+    // there are no parameters, but misspelled ones are still reported
+    Params::new("repl").validate();
+    println!("type a pipeline, `help` or `quit`");
+
+    let stdin = io::stdin();
+    repl(stdin.lock(), &mut io::stdout(), execute).unwrap();
+}
+
+// Reads pipelines from `input` until its end or `quit`, and runs each of them; prompts and
+// errors go to `out`. A line that is not valid UTF-8 is reported and skipped, and failing to
+// read ends the input.
+fn repl<R: BufRead, W: Write, F: FnMut(Pipeline)>(mut input: R, out: &mut W, mut run: F) -> io::Result<()> {
+    loop {
+        write!(out, "> ")?;
+        out.flush()?;
+
+        let mut line = String::new();
+        match input.read_line(&mut line) {
+            // end of input
+            Ok(0) => {
+                writeln!(out)?;
+                return Ok(());
+            },
+            Ok(_) => (),
+            Err(ref error) if error.kind() == io::ErrorKind::InvalidData => {
+                writeln!(out, "error: the line is not valid UTF-8")?;
+                continue;
+            },
+            Err(error) => {
+                writeln!(out, "error: cannot read the input: {}", error)?;
+                return Ok(());
+            },
+        }
+
+        match line.trim() {
+            "" => (),
+            "help" => writeln!(out, "{}", HELP)?,
+            "quit" | "exit" => return Ok(()),
+            text => match Pipeline::parse(text) {
+                Ok(pipeline) => run(pipeline),
+                Err(error) => writeln!(out, "error: {}", error)?,
+            },
+        }
+    }
+}
+
+fn execute(pipeline: Pipeline) {
+    runner::execute_from_args(std::env::args(), move |worker| {
+        let index = worker.index();
+        // the dataflow is compiled from the parsed pipeline instead of being fixed in code
+        worker.dataflow::<(),_,_>(|scope| pipeline.build(scope, index));
+    }).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, BufRead, Cursor, Read};
+
+    use super::repl;
+    use crate::examples::interactive::pipeline::Pipeline;
+
+    // the pipelines `input` runs, and what it prints
+    fn session<R: BufRead>(input: R) -> (Vec<Pipeline>, String) {
+        let mut out = Vec::new();
+        let mut pipelines = Vec::new();
+        repl(input, &mut out, |pipeline| pipelines.push(pipeline)).unwrap();
+        (pipelines, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn runs_each_pipeline_until_quit() {
+        let (pipelines, out) = session(Cursor::new("range 0 3\n\nsort\nrange 1 2 | inspect\nquit\nrange 5 6\n"));
+        assert_eq!(pipelines, vec![Pipeline::parse("range 0 3").unwrap(), Pipeline::parse("range 1 2 | inspect").unwrap()]);
+        assert_eq!(out, "> > > error: stage 1: a pipeline must start with `range <from> <to>`\n> > ");
+    }
+
+    #[test]
+    fn invalid_utf8_is_reported_and_skipped() {
+        let (pipelines, out) = session(Cursor::new(b"range 0 \xff\nrange 0 1\n".to_vec()));
+        assert_eq!(pipelines, vec![Pipeline::parse("range 0 1").unwrap()]);
+        assert_eq!(out, "> error: the line is not valid UTF-8\n> > \n");
+    }
+
+    // fails every read
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("broken pipe"))
+        }
+    }
+
+    #[test]
+    fn a_read_error_ends_the_input() {
+        let (pipelines, out) = session(io::BufReader::new(Broken));
+        assert!(pipelines.is_empty());
+        assert_eq!(out, "> error: cannot read the input: broken pipe\n");
+    }
+}
@@ -1,9 +1,16 @@
 extern crate timely;
 use timely::dataflow::operators::{ToStream, Inspect};
 
+use crate::examples::params::Params;
+use crate::examples::runner;
+
 pub fn run() {
-    timely::example(|scope| {
-        (0..10)
+    let mut params = Params::new("hello_world");
+    let range = params.range("range", 0..10);
+    params.validate();
+
+    runner::example(move |scope| {
+        range.clone()
             .to_stream(scope)
             .inspect(|x| {
                 println!("seen: {:?}", x)
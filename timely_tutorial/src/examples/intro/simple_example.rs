@@ -3,9 +3,16 @@ extern crate timely;
 use timely::dataflow::InputHandle;
 use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
 
+use crate::examples::params::Params;
+use crate::examples::runner;
+
 pub fn run() {
+    let mut params = Params::new("simple_example");
+    let rounds = params.get("rounds", 10u64);
+    params.validate();
+
     // initializes and runs a timely dataflow.
-    timely::execute_from_args(std::env::args(), |worker| {
+    runner::execute_from_args(std::env::args(), move |worker| {
         let index = worker.index();
         let mut input = InputHandle::new();
 
@@ -18,7 +25,7 @@ pub fn run() {
         );
 
         // introduce data and watch!
-        for round in 0..10 {
+        for round in 0..rounds {
             if index == 0 {
                 input.send(round);
             }
@@ -2,3 +2,11 @@ pub mod intro;
 pub mod core;
 pub mod building;
 pub mod advanced;
+pub mod interactive;
+pub mod args;
+pub mod params;
+pub mod graph;
+pub mod runner;
+pub mod simulation;
+pub mod dashboard;
+pub mod faults;
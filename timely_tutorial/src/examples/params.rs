@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::process;
+use std::str::FromStr;
+
+use crate::examples::args;
+
+// arguments read by `main` rather than by the examples
+const GLOBAL: [&str; 4] = ["name", "config", "format", "out"];
+
+/// Typed parameters of an example.
+///
+/// An example declares each parameter with its default; the value is taken, in order, from a
+/// `key=value` argument, from the table named after the example in the file given by
+/// `config=<file>`, or from the default:
+///
+/// ```toml
+/// [word_count]
+/// rounds = 20
+///
+/// [operators]
+/// range = "0..8"
+/// partitions = 4
+/// ```
+///
+/// Malformed values, failed checks, and keys of the table or `key=value` arguments that the example
+/// does not declare are collected, and reported together by `validate`, before any dataflow
+/// starts.
+pub struct Params {
+    example: String,
+    // the table of the example in the config file, as unquoted values
+    table: HashMap<String, String>,
+    declared: Vec<String>,
+    errors: Vec<String>,
+}
+
+impl Params {
+    pub fn new(example: &str) -> Params {
+        let mut params = Params {
+            example: example.to_owned(),
+            table: HashMap::new(),
+            declared: Vec::new(),
+            errors: Vec::new(),
+        };
+        if let Some(path) = args::value("config") {
+            match fs::read_to_string(&path).map_err(|error| error.to_string()).and_then(|text| parse(&text)) {
+                Ok(mut tables) => params.table = tables.remove(example).unwrap_or_default(),
+                Err(error) => params.errors.push(format!("{}: {}", path, error)),
+            }
+        }
+        params
+    }
+
+    /// A parameter parsed with `FromStr`.
+    pub fn get<T: FromStr>(&mut self, key: &str, default: T) -> T {
+        self.optional(key).unwrap_or(default)
+    }
+
+    /// A parameter without a default, e.g. an optional directory.
+    pub fn optional<T: FromStr>(&mut self, key: &str) -> Option<T> {
+        self.declared.push(key.to_owned());
+        let (source, value) = match args::value(key) {
+            Some(value) => ("argument", value),
+            None => match self.table.get(key) {
+                Some(value) => ("config", value.clone()),
+                None => return None,
+            },
+        };
+        match value.parse() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                self.errors.push(format!("{} `{}`: invalid value `{}`", source, key, value));
+                None
+            },
+        }
+    }
+
+    /// A range written as `start..end`.
+    pub fn range(&mut self, key: &str, default: Range<u64>) -> Range<u64> {
+        let text = self.get(key, format!("{}..{}", default.start, default.end));
+        let bounds = text.split("..").map(|bound| bound.trim().parse::<u64>()).collect::<Vec<_>>();
+        match bounds[..] {
+            [Ok(start), Ok(end)] => {
+                self.check(start <= end, &format!("`{}` must not end before it starts", key));
+                start..end
+            },
+            _ => {
+                self.errors.push(format!("`{}`: invalid range `{}`, expected `start..end`", key, text));
+                default
+            },
+        }
+    }
+
+    /// Records `message` as an error unless `valid` holds.
+    pub fn check(&mut self, valid: bool, message: &str) {
+        if !valid {
+            self.errors.push(message.to_owned());
+        }
+    }
+
+    /// Exits, listing the errors, if any parameter was invalid.
+    pub fn validate(self) {
+        let mut unknown = self.table.keys()
+            .filter(|key| !self.declared.contains(key))
+            .map(|key| format!("config `{}`: unknown parameter, expected one of {:?}", key, self.declared))
+            .collect::<Vec<_>>();
+        unknown.sort();
+        let args = std::env::args().collect::<Vec<_>>();
+        unknown.extend(args.iter()
+            .filter_map(|arg| args::split(arg))
+            .filter(|(key, _)| !self.declared.iter().any(|declared| declared == key) && !GLOBAL.contains(key))
+            .map(|(key, _)| format!("argument `{}`: unknown parameter, expected one of {:?}", key, self.declared)));
+
+        if !self.errors.is_empty() || !unknown.is_empty() {
+            eprintln!("invalid parameters for `{}`:", self.example);
+            for error in self.errors.iter().chain(unknown.iter()) {
+                eprintln!("  {}", error);
+            }
+            process::exit(2);
+        }
+    }
+}
+
+// The subset of TOML used for parameters: `[table]` headers and `key = value` pairs, where the
+// value is an integer, a float, a boolean or a basic string, and `#` starts a comment.
+fn parse(text: &str) -> Result<HashMap<String, HashMap<String, String>>, String> {
+    let mut tables = HashMap::<String, HashMap<String, String>>::new();
+    let mut table = None;
+
+    for (number, line) in text.lines().enumerate() {
+        let error = |message: &str| format!("line {}: {}", number + 1, message);
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            let name = line.strip_prefix('[').and_then(|line| line.strip_suffix(']'))
+                .map(|name| name.trim())
+                .filter(|name| is_key(name))
+                .ok_or_else(|| error("invalid table header"))?;
+            if tables.contains_key(name) {
+                return Err(error(&format!("table `{}` defined twice", name)));
+            }
+            tables.insert(name.to_owned(), HashMap::new());
+            table = Some(name.to_owned());
+            continue;
+        }
+
+        let (key, value) = match line.find('=') {
+            Some(pos) => (line[..pos].trim(), line[pos + 1..].trim()),
+            None => return Err(error("expected `key = value`")),
+        };
+        if !is_key(key) {
+            return Err(error(&format!("invalid key `{}`", key)));
+        }
+        let value = if value.starts_with('"') {
+            unquote(value).ok_or_else(|| error("invalid string"))?
+        } else if is_number(value) || value == "true" || value == "false" {
+            value.replace('_', "")
+        } else {
+            return Err(error(&format!("unsupported value `{}`", value)));
+        };
+
+        let name = table.as_ref().ok_or_else(|| error("parameters must be inside a `[example]` table"))?;
+        let entries = tables.get_mut(name).unwrap();
+        if entries.insert(key.to_owned(), value).is_some() {
+            return Err(error(&format!("key `{}` defined twice", key)));
+        }
+    }
+    Ok(tables)
+}
+
+// an integer or a float, possibly with `_` between digits; `inf` and `nan` are not parameters
+fn is_number(value: &str) -> bool {
+    let digits = value.strip_prefix(|c| c == '+' || c == '-').unwrap_or(value);
+    digits.starts_with(|c: char| c.is_ascii_digit()) && digits.replace('_', "").parse::<f64>().is_ok()
+}
+
+fn is_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+// the line up to a `#` that is not inside a string
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (pos, c) in line.char_indices() {
+        match c {
+            '\\' if quoted => { escaped = !escaped; continue; },
+            '"' if !escaped => quoted = !quoted,
+            '#' if !quoted => return &line[..pos],
+            _ => (),
+        }
+        escaped = false;
+    }
+    line
+}
+
+fn unquote(value: &str) -> Option<String> {
+    let mut chars = value[1..].chars();
+    let mut unquoted = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return if chars.as_str().is_empty() { Some(unquoted) } else { None },
+            '\\' => match chars.next()? {
+                'n' => unquoted.push('\n'),
+                't' => unquoted.push('\t'),
+                c @ '"' | c @ '\\' => unquoted.push(c),
+                _ => return None,
+            },
+            c => unquoted.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn values_are_numbers_booleans_or_strings() {
+        let tables = parse("[a]\nx = 1_000\ny = -2.5\nz = true\nw = \"0..5\" # comment\n").unwrap();
+        let a = &tables["a"];
+        assert_eq!(a["x"], "1000");
+        assert_eq!(a["y"], "-2.5");
+        assert_eq!(a["z"], "true");
+        assert_eq!(a["w"], "0..5");
+        for value in ["inf", "-inf", "nan", "NaN", "infinity", "_1", "yes"] {
+            assert!(parse(&format!("[a]\nx = {}\n", value)).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn errors_give_the_line() {
+        assert_eq!(parse("[a]\nx = 1\nx = 2\n").unwrap_err(), "line 3: key `x` defined twice");
+        assert_eq!(parse("x = 1\n").unwrap_err(), "line 1: parameters must be inside a `[example]` table");
+    }
+}
@@ -0,0 +1,70 @@
+extern crate timely;
+
+use std::sync::Mutex;
+
+use timely::communication::{Allocate, Allocator, WorkerGuards};
+use timely::dataflow::scopes::Child;
+use timely::logging::TimelyEvent;
+use timely::worker::Worker;
+use timely::Configuration;
+
+use crate::examples::graph::Topology;
+
+// The examples start their computations through this module instead of calling `timely::example`
+// and `timely::execute_from_args` directly; both behave exactly like timely's, except that while
+// a capture is active the first worker of each computation records the dataflow topology.
+static CAPTURE: Mutex<Option<Vec<Topology>>> = Mutex::new(None);
+
+/// Same as `timely::example`.
+pub fn example<T, F>(func: F) -> T
+where
+    T: Send+'static,
+    F: Fn(&mut Child<Worker<Allocator>, u64>)->T+Send+Sync+'static {
+    timely::execute(Configuration::Thread, move |worker| {
+        record(worker);
+        worker.dataflow(|scope| func(scope))
+    }).unwrap().join().pop().unwrap().unwrap()
+}
+
+/// Same as `timely::execute_from_args`.
+pub fn execute_from_args<I, T, F>(iter: I, func: F) -> Result<WorkerGuards<T>, String>
+where
+    I: Iterator<Item=String>,
+    T: Send+'static,
+    F: Fn(&mut Worker<Allocator>)->T+Send+Sync+'static {
+    timely::execute_from_args(iter, move |worker| {
+        record(worker);
+        func(worker)
+    })
+}
+
+/// Runs `func` and returns the topology of every computation it started, in order.
+pub fn capture<F: FnOnce()>(func: F) -> Vec<Topology> {
+    *CAPTURE.lock().unwrap() = Some(Vec::new());
+    func();
+    CAPTURE.lock().unwrap().take().unwrap()
+}
+
+// installs a logger recording operators and channels, before any dataflow is built; the
+// topology is the same on each worker, so only the first one records it
+fn record<A: Allocate>(worker: &mut Worker<A>) {
+    if worker.index() != 0 {
+        return;
+    }
+
+    let computation = match CAPTURE.lock().unwrap().as_mut() {
+        Some(topologies) => {
+            topologies.push(Topology::default());
+            topologies.len() - 1
+        },
+        None => return,
+    };
+
+    worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+        if let Some(topologies) = CAPTURE.lock().unwrap().as_mut() {
+            for (_, _, event) in data.drain(..) {
+                topologies[computation].record(event);
+            }
+        }
+    });
+}
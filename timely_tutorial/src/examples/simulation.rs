@@ -0,0 +1,94 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::communication::allocator::Process;
+use timely::dataflow::scopes::Child;
+use timely::progress::timestamp::{Refines, Timestamp};
+use timely::worker::Worker;
+
+// `timely::example` and `execute_from_args` run each worker in its own thread, so the order in
+// which workers print depends on the OS scheduler. A simulation runs several logical workers in
+// the current thread instead; they still exchange data through the in-process allocator, but
+// the next worker to step is picked by a seeded generator, so a seed always gives the same order.
+//
+// Note that operators keeping state in a `HashMap` may still iterate it in a different order in
+// each run, since its hasher is randomly seeded.
+pub struct Simulation {
+    workers: Vec<Worker<Process>>,
+    // xorshift state; never 0
+    state: u64,
+}
+
+impl Simulation {
+    pub fn new(peers: usize, seed: u64) -> Simulation {
+        // splitmix64 spreads nearby seeds apart, and is one-to-one, so only the seed it maps to 0
+        // shares its state with another one
+        let mut state = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        state ^= state >> 31;
+        Simulation {
+            workers: Process::new_vector(peers).into_iter().map(Worker::new).collect(),
+            state: if state == 0 { 0x9e37_79b9_7f4a_7c15 } else { state },
+        }
+    }
+
+    // builds the same dataflow on each worker, in index order, returning what each built
+    pub fn dataflow<T, R, F>(&mut self, mut func: F) -> Vec<R>
+    where
+        T: Timestamp+Refines<()>,
+        F: FnMut(usize, &mut Child<Worker<Process>, T>)->R {
+        self.workers
+            .iter_mut()
+            .enumerate()
+            .map(|(index, worker)| worker.dataflow(|scope| func(index, scope)))
+            .collect()
+    }
+
+    // steps the next worker picked by the scheduler and returns its index
+    pub fn step(&mut self) -> usize {
+        let index = (self.next() % self.workers.len() as u64) as usize;
+        self.workers[index].step();
+        index
+    }
+
+    pub fn step_while<F: FnMut()->bool>(&mut self, mut func: F) {
+        while func() { self.step(); }
+    }
+
+    // steps the workers until their dataflows complete, i.e. until all inputs were dropped
+    pub fn run(&mut self) {
+        let mut active = vec![true; self.workers.len()];
+        while active.iter().any(|active| *active) {
+            let index = (self.next() % self.workers.len() as u64) as usize;
+            active[index] = self.workers[index].step();
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+}
+
+// Lines printed by a simulated dataflow, kept so that they can be compared afterwards. All the
+// workers share the thread, so a plain `Rc` is enough.
+#[derive(Clone, Default)]
+pub struct Trace {
+    lines: Rc<RefCell<Vec<String>>>,
+}
+
+impl Trace {
+    pub fn record(&self, line: String) {
+        println!("{}", line);
+        self.lines.borrow_mut().push(line);
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.borrow().clone()
+    }
+}
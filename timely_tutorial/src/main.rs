@@ -1,50 +1,118 @@
+#[macro_use]
+extern crate abomonation_derive;
+
 use std::env;
+use std::fs;
+use std::process;
 
 mod examples;
 use examples::intro::{simple_example, hello_world};
-use examples::core::{dataflow, timestamps, progress};
-use examples::building::{inputs, outputs, operators, creating_operators, word_count};
-use examples::advanced::{iteration};
-
-fn extract(args: Vec<String>) -> Option<String> {
-    for arg in args.iter() {
-        if let Some(pos) = arg.find("name=") {
-            let example = String::from(&arg[(pos + 5)..]);
-            return Some(example)
-        }
-    }
-    return None
-}
+use examples::core::{dataflow, timestamps, progress, simulation, custom_timestamps};
+use examples::building::{inputs, outputs, operators, creating_operators, word_count, rate_limited, file_sink, collectives, sorting, fault_injection};
+use examples::advanced::{iteration, sessionization, attribution};
+use examples::interactive::repl;
+use examples::args::extract;
+use examples::graph::{self, Format};
+use examples::runner;
+
+// the examples `run` knows, as listed by errors; most also have an alias with `_` for `-`
+const EXAMPLES: [&str; 21] = [
+    "hello-world", "simple-example",
+    "dataflow", "timestamps", "custom-timestamps", "progress", "simulation",
+    "inputs", "outputs", "operators", "creating-operators", "rate-limited", "file-sink",
+    "collectives", "sorting", "fault-injection", "word-count",
+    "iteration", "sessionization", "attribution",
+    "repl",
+];
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if let Some(example) = extract(args) {
-        match example.as_ref() {
-            // intro
-            "hello-world" => hello_world::run(),
-            "hello_world" => hello_world::run(),
-            "simple-example" => simple_example::run(),
-            "simple_example" => simple_example::run(),
-
-            // core concepts
-            "dataflow" => dataflow::run(),
-            "timestamps" => timestamps::run(),
-            "progress" => progress::run(),
-
-            // building dataflows
-            "inputs" => inputs::run(),
-            "outputs" => outputs::run(),
-            "operators" => operators::run(),
-            "creating_operators" => creating_operators::run(),
-            "creating-operators" => creating_operators::run(),
-            "word-count" => word_count::run(),
-            "word_count" => word_count::run(),
-
-            // advanced dataflos
-            "iteration" => iteration::run(),
-            "iterations" => iteration::run(),
-
-            _ => (),
+    if args.get(1).map(String::as_str) == Some("graph") {
+        // `graph <example> [format=dot|mermaid] [out=<file>]` runs the example and exports the
+        // dataflows it constructed
+        let example = match args.get(2) {
+            Some(example) => example,
+            None => usage("`graph` expects the name of an example"),
+        };
+        let format = match extract(&args, "format").as_deref() {
+            Some("dot") | None => Format::Dot,
+            Some("mermaid") => Format::Mermaid,
+            Some(format) => {
+                eprintln!("unknown format `{}`, expected `dot` or `mermaid`", format);
+                process::exit(2);
+            },
+        };
+        let extension = if format == Format::Dot { "dot" } else { "mmd" };
+        let out = extract(&args, "out").unwrap_or(format!("{}.{}", example, extension));
+
+        let mut known = false;
+        let topologies = runner::capture(|| known = run(example));
+        if !known {
+            usage(&format!("unknown example `{}`", example));
+        }
+        if let Err(error) = fs::write(&out, graph::render(&topologies, format)) {
+            eprintln!("cannot write {}: {}", out, error);
+            process::exit(1);
         }
+        eprintln!("dataflow graph written to {}", out);
+    } else if let Some(example) = extract(&args, "name") {
+        if !run(&example) {
+            usage(&format!("unknown example `{}`", example));
+        }
+    }
+}
+
+fn usage(error: &str) -> ! {
+    eprintln!("{}, expected one of: {}", error, EXAMPLES.join(", "));
+    process::exit(2);
+}
+
+// runs `example`, or returns false if there is no such example
+fn run(example: &str) -> bool {
+    match example {
+        // intro
+        "hello-world" => hello_world::run(),
+        "hello_world" => hello_world::run(),
+        "simple-example" => simple_example::run(),
+        "simple_example" => simple_example::run(),
+
+        // core concepts
+        "dataflow" => dataflow::run(),
+        "timestamps" => timestamps::run(),
+        "custom-timestamps" => custom_timestamps::run(),
+        "custom_timestamps" => custom_timestamps::run(),
+        "progress" => progress::run(),
+        "simulation" => simulation::run(),
+
+        // building dataflows
+        "inputs" => inputs::run(),
+        "outputs" => outputs::run(),
+        "operators" => operators::run(),
+        "creating_operators" => creating_operators::run(),
+        "creating-operators" => creating_operators::run(),
+        "word-count" => word_count::run(),
+        "word_count" => word_count::run(),
+        "rate-limited" => rate_limited::run(),
+        "rate_limited" => rate_limited::run(),
+        "file-sink" => file_sink::run(),
+        "file_sink" => file_sink::run(),
+        "collectives" => collectives::run(),
+        "sorting" => sorting::run(),
+        "sort" => sorting::run(),
+        "fault-injection" => fault_injection::run(),
+        "fault_injection" => fault_injection::run(),
+
+        // advanced dataflos
+        "iteration" => iteration::run(),
+        "iterations" => iteration::run(),
+        "sessionization" => sessionization::run(),
+        "sessions" => sessionization::run(),
+        "attribution" => attribution::run(),
+
+        // interactive
+        "repl" => repl::run(),
+
+        _ => return false,
     }
+    true
 }